@@ -0,0 +1,223 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, LitStr};
+
+struct Field {
+    ident: syn::Ident,
+    ty: syn::Type,
+    name: String,
+    skip: bool,
+}
+
+fn fields(input: &DeriveInput) -> syn::Result<Vec<Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            input,
+            "TreeEditable can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &data.fields,
+            "TreeEditable requires named fields",
+        ));
+    };
+
+    fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident = field.ident.clone().unwrap();
+            let mut name = ident.to_string();
+            let mut skip = false;
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|a| a.path().is_ident("tree_edit"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        name = meta.value()?.parse::<LitStr>()?.value();
+                        Ok(())
+                    } else if meta.path.is_ident("skip") {
+                        skip = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `rename = \"...\"` or `skip`"))
+                    }
+                })?;
+            }
+            Ok(Field {
+                ident,
+                ty: field.ty.clone(),
+                name,
+                skip,
+            })
+        })
+        .collect()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "TreeEditable does not support generic structs",
+        ));
+    }
+
+    let ident = &input.ident;
+    let fields = fields(&input)?;
+    let (skipped, fields): (Vec<_>, Vec<_>) = fields.into_iter().partition(|field| field.skip);
+
+    let names = fields.iter().map(|field| &field.name);
+    let values = fields.iter().map(|Field { ident, name, .. }| {
+        quote! {
+            .value(#name, "Value", ::tui_va_tree_edit::Value::from(
+                ::core::clone::Clone::clone(&self.#ident)
+            ))
+        }
+    });
+    let extract = fields.iter().map(|Field { ident, name, .. }| {
+        quote! {
+            #ident: args
+                .get_value(#name, "Value")
+                .ok_or_else(|| ::tui_va_tree_edit::ValueError::Missing(#name.into()))
+                .and_then(::tui_va_tree_edit::FromValue::from_value)?
+        }
+    });
+    let bounds = fields
+        .iter()
+        .map(|Field { ty, .. }| {
+            quote_spanned! {ty.span()=>
+                #ty: ::core::clone::Clone
+                    + ::tui_va_tree_edit::FromValue
+                    + ::core::convert::Into<::tui_va_tree_edit::Value<'static>>
+            }
+        })
+        .chain(skipped.iter().map(|Field { ty, .. }| {
+            quote_spanned! {ty.span()=> #ty: ::core::default::Default }
+        }))
+        .collect::<Vec<_>>();
+    let skipped = skipped.iter().map(|Field { ident, .. }| {
+        quote! { #ident: ::core::default::Default::default() }
+    });
+
+    Ok(quote! {
+        impl ::tui_va_tree_edit::TreeEditable for #ident
+        where
+            #(#bounds,)*
+        {
+            fn to_branch<'a>(&self) -> ::tui_va_tree_edit::Branch<'a> {
+                ::tui_va_tree_edit::Args::default()
+                    .names([#(#names),*])
+                    .columns(["Value"])
+                    #(#values)*
+                    .into()
+            }
+            fn from_branch(
+                branch: &::tui_va_tree_edit::Branch,
+            ) -> ::core::result::Result<Self, ::tui_va_tree_edit::ValueError> {
+                let args = branch
+                    .as_args()
+                    .ok_or(::tui_va_tree_edit::ValueError::Branch)?;
+                ::core::result::Result::Ok(Self {
+                    #(#extract,)*
+                    #(#skipped,)*
+                })
+            }
+        }
+
+        impl ::tui_va_tree_edit::GetType for #ident {
+            fn get() -> ::tui_va_tree_edit::Type {
+                ::tui_va_tree_edit::Type::Struct
+            }
+        }
+
+        impl ::tui_va_tree_edit::FromValue for #ident {
+            fn from_value(
+                value: &::tui_va_tree_edit::Value,
+            ) -> ::core::result::Result<Self, ::tui_va_tree_edit::ValueError> {
+                value
+                    .as_struct()
+                    .ok_or_else(|| ::tui_va_tree_edit::ValueError::Mismatch {
+                        expected: ::tui_va_tree_edit::Type::Struct,
+                        found: value.get_type().clone(),
+                    })
+                    .and_then(<Self as ::tui_va_tree_edit::TreeEditable>::from_branch)
+            }
+        }
+
+        impl ::core::convert::TryFrom<&::tui_va_tree_edit::Value<'_>> for #ident {
+            type Error = ::tui_va_tree_edit::ValueError;
+
+            fn try_from(
+                value: &::tui_va_tree_edit::Value<'_>,
+            ) -> ::core::result::Result<Self, Self::Error> {
+                ::tui_va_tree_edit::FromValue::from_value(value)
+            }
+        }
+
+        impl<'a> ::core::convert::From<#ident> for ::tui_va_tree_edit::Value<'a> {
+            fn from(value: #ident) -> Self {
+                ::tui_va_tree_edit::TreeEditable::to_branch(&value).into()
+            }
+        }
+    })
+}
+
+/// Every field type must be `Clone`, `FromValue` and convertible into `Value`;
+/// structs stored in a `Vec` also need `Default` for the element layout, and
+/// `#[tree_edit(skip)]` fields are rebuilt with `Default`. The generated impl
+/// carries these as where-bounds, so a missing trait is reported on the field.
+#[proc_macro_derive(TreeEditable, attributes(tree_edit))]
+pub fn derive_tree_editable(input: TokenStream) -> TokenStream {
+    expand(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, TokenStream2};
+    use quote::quote;
+    use syn::parse_quote;
+
+    #[test]
+    fn it_works() {
+        let tokens = expand(parse_quote! {
+            struct Port {
+                number: u16,
+                #[tree_edit(rename = "Open")]
+                open: bool,
+                #[tree_edit(skip)]
+                cache: Vec<u8>,
+            }
+        })
+        .unwrap()
+        .to_string();
+        let contains = |expected: TokenStream2| tokens.contains(&expected.to_string());
+        assert!(contains(quote!(.names(["number", "Open"]))));
+        assert!(contains(quote!(u16: ::core::clone::Clone)));
+        assert!(contains(quote!(Vec<u8>: ::core::default::Default)));
+        assert!(contains(quote!(cache: ::core::default::Default::default())));
+
+        let error = |input| expand(input).unwrap_err().to_string();
+        assert_eq!(
+            error(parse_quote! { enum Port { Open } }),
+            "TreeEditable can only be derived for structs"
+        );
+        assert_eq!(
+            error(parse_quote! { struct Port(u16); }),
+            "TreeEditable requires named fields"
+        );
+        assert_eq!(
+            error(parse_quote! { struct Port<T> { number: T } }),
+            "TreeEditable does not support generic structs"
+        );
+        assert_eq!(
+            error(parse_quote! { struct Port { #[tree_edit(hidden)] number: u16 } }),
+            "expected `rename = \"...\"` or `skip`"
+        );
+    }
+}
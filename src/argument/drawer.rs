@@ -1,4 +1,8 @@
-use crate::{argument::value::ValueVariant, state::State, widget::DrawerRef, Args};
+use crate::{
+    argument::value::ValueVariant,
+    widget::{Context, DrawerRef},
+    Access, Args, Branch, Value,
+};
 use std::{
     ops::Deref,
     sync::{
@@ -8,19 +12,24 @@ use std::{
 };
 use tui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, Paragraph, Widget},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+    },
 };
 use tui_textarea::TextArea;
 
-type DState<'s, 'u> = (&'s State, &'u mut usize);
+type DState<'s, 'u> = (&'s Context<'s>, &'u mut usize);
+
+const EDITOR_LINES: usize = 3;
 
 struct ToRenderImpl<'c, 's, 'r, 'a, 'u> {
     current: &'c Option<(usize, usize, Arc<AtomicUsize>)>,
     state: &'s DState<'r, 'u>,
     col_index: usize,
     values: Option<Vec<(String, Text<'a>)>>,
+    accesses: Vec<Access>,
 }
 impl<'c, 's, 'r, 'a, 'u> ToRenderImpl<'c, 's, 'r, 'a, 'u> {
     fn highlight_style(&self, index: usize) -> Option<Style> {
@@ -32,6 +41,10 @@ impl<'c, 's, 'r, 'a, 'u> ToRenderImpl<'c, 's, 'r, 'a, 'u> {
             .then_some(self.state.0.highlight_style)
         })
     }
+    fn style(&self, index: usize) -> Style {
+        let access = self.accesses.get(index).copied().unwrap_or_default();
+        self.state.0.style.patch(self.state.0.access_style(access))
+    }
     fn text(
         &self,
         index: usize,
@@ -48,7 +61,14 @@ impl<'c, 's, 'r, 'a, 'u> ToRenderImpl<'c, 's, 'r, 'a, 'u> {
                 .border_style(Style::default().add_modifier(Modifier::ITALIC)),
         );
         if !cursor_style {
-            _text.set_style(self.highlight_style(index).unwrap_or(self.state.0.style));
+            let matched = self.state.0.is_match(&_text.lines().join("\n"));
+            _text.set_style(
+                self.highlight_style(index)
+                    .or_else(|| {
+                        matched.then_some(self.style(index).patch(self.state.0.match_style))
+                    })
+                    .unwrap_or_else(|| self.style(index)),
+            );
         }
         let style = _text.style();
         _text.set_cursor_style(if !cursor_style {
@@ -65,6 +85,23 @@ impl<'c, 's, 'r, 'a, 'u> ToRenderImpl<'c, 's, 'r, 'a, 'u> {
         ToRender::Text(text)
     }
     fn spans(&self, index: usize, span: Span<'a>) -> Spans<'a> {
+        let renaming = self.col_index == 0
+            && self.state.0.key.is_some()
+            && *self.state.1 + 1 == self.state.0.position.len()
+            && self
+                .current
+                .as_ref()
+                .map_or(false, |(row, _, _)| *row == index);
+        if renaming {
+            let (key, cursor) = span.content.split_at(span.content.len().saturating_sub(1));
+            return Spans::from(vec![
+                Span::styled(key.to_string(), span.style),
+                Span::styled(
+                    cursor.to_string(),
+                    Style::default().add_modifier(Modifier::REVERSED),
+                ),
+            ]);
+        }
         if let Some(style) = self.current.as_ref().and_then(|(row, col, _)| {
             ((index == 0 && self.col_index == *col && self.state.0.input.is_none()
                 || self.col_index == 0 && index == *row && self.state.0.input.is_none()
@@ -73,10 +110,15 @@ impl<'c, 's, 'r, 'a, 'u> ToRenderImpl<'c, 's, 'r, 'a, 'u> {
             .then_some(self.state.0.highlight_style)
         }) {
             Span::styled(span.content, style)
+        } else if self.state.0.is_match(&span.content) {
+            Span::styled(
+                span.content,
+                self.style(index).patch(self.state.0.match_style),
+            )
         } else if index == 0 || self.col_index == 0 {
             Span::styled(span.content, Style::default().add_modifier(Modifier::DIM))
         } else {
-            span
+            Span::styled(span.content, self.style(index))
         }
         .into()
     }
@@ -157,23 +199,32 @@ impl<'r, 'a> Drawer<'r, 'a> {
             })
         })
     }
-    fn values(&self) -> Vec<Vec<(String, Text<'a>)>> {
+    fn values(&self, state: &DState) -> Vec<Vec<(String, Text<'a>)>> {
         self.columns
             .iter()
-            .map(|column| {
+            .enumerate()
+            .map(|(col, column)| {
                 [("".to_string(), Text::Span(Span::from(column.clone())))]
                     .into_iter()
                     .chain(self.names.iter().map(|name| {
-                        self.get_value(name, column).map_or(
+                        self.get_value(name, column).map(Value::inner).map_or(
                             ("".to_string(), Text::Span(Span::from(""))),
                             |value| {
+                                let mut title = value.title();
+                                if state.0.is_dirty(*state.1, |node| {
+                                    node.as_args().map_or(false, |(row, column, _)| {
+                                        row == name && *column == col
+                                    })
+                                }) {
+                                    title.push('*');
+                                }
                                 (
-                                    value.0.to_string(),
+                                    title,
                                     match &value.1 {
                                         ValueVariant::TextArea(text) => {
                                             Text::<'a>::Text(text.clone())
                                         }
-                                        value => Text::Span(Span::from(value.to_string())),
+                                        _ => Text::Span(Span::from(value.to_string())),
                                     },
                                 )
                             },
@@ -183,44 +234,173 @@ impl<'r, 'a> Drawer<'r, 'a> {
             })
             .collect()
     }
-    fn names(&self) -> Vec<(String, Text<'a>)> {
-        ["".to_string()]
+    fn names(&self, state: &DState) -> Vec<(String, Text<'a>)> {
+        let key = state
+            .0
+            .key
+            .filter(|_| *state.1 + 1 == state.0.position.len())
+            .zip(self.current(state).map(|(row, _, _)| row - 1));
+        [Span::from("")]
+            .into_iter()
+            .chain(self.names.iter().enumerate().map(|(row, name)| match key {
+                Some((key, current)) if current == row => {
+                    let color = if key.is_empty() || key != name && self.names.contains(key) {
+                        Color::Red
+                    } else {
+                        Color::Green
+                    };
+                    Span::styled(format!("{key} "), Style::default().fg(color))
+                }
+                _ if state.0.is_dirty(*state.1, |node| {
+                    node.as_args().map_or(false, |(row, _, _)| row == name)
+                }) =>
+                {
+                    Span::from(format!("{name}*"))
+                }
+                _ => Span::from(name.clone()),
+            }))
+            .map(|name| ("".to_string(), Text::Span(name)))
+            .collect()
+    }
+    fn widths(names: &Vec<(String, Text<'a>)>, values: &[Vec<(String, Text<'a>)>]) -> Vec<usize> {
+        [names]
             .into_iter()
-            .chain(self.names.iter().cloned())
-            .map(|name| ("".to_string(), Text::Span(Span::from(name))))
+            .chain(values.iter())
+            .map(|values| {
+                let len = values.iter().fold(0usize, |max, (name, value)| {
+                    max.max(name.len() + 1).max(match value {
+                        Text::Text(text) => text
+                            .lock()
+                            .unwrap()
+                            .lines()
+                            .iter()
+                            .map(|str| str.len())
+                            .max()
+                            .unwrap_or(0),
+                        Text::Span(span) => span.width(),
+                    })
+                });
+                if len > 1 {
+                    len + 3
+                } else {
+                    0
+                }
+            })
+            .collect()
+    }
+    fn width(&self, state: &DState) -> u16 {
+        Self::widths(&self.names(state), &self.values(state))
+            .into_iter()
+            .fold(1usize, |res, width| res + width)
+            .min(u16::MAX as usize) as u16
+    }
+    fn branch(&self, current: Option<(usize, usize, Arc<AtomicUsize>)>) -> Option<&Branch<'a>> {
+        current.and_then(|(name, col, _)| {
+            self.get_value_by_indexes(name - 1, col - 1)
+                .and_then(|value| match &value.inner().1 {
+                    ValueVariant::Struct(b) => Some(b),
+                    _ => None,
+                })
+        })
+    }
+    fn access(&self, state: &DState, current: &Option<(usize, usize, Arc<AtomicUsize>)>) -> Access {
+        let access = state.0.access.get().max(self.get_access());
+        current
+            .as_ref()
+            .and_then(|(row, col, _)| self.get_value_by_indexes(row - 1, col - 1))
+            .map_or(access, |value| access.max(value.get_access()))
+    }
+    fn accesses(&self, state: &DState, col_index: usize) -> Vec<Access> {
+        let access = state.0.access.get().max(self.get_access());
+        [access]
+            .into_iter()
+            .chain((0..self.names.len()).map(|row| {
+                col_index
+                    .checked_sub(1)
+                    .and_then(|col| self.get_value_by_indexes(row, col))
+                    .map_or(access, |value| access.max(value.get_access()))
+            }))
+            .collect()
+    }
+    fn heights(
+        &self,
+        state: &DState,
+        current: &Option<(usize, usize, Arc<AtomicUsize>)>,
+    ) -> Vec<u16> {
+        let editing = current
+            .as_ref()
+            .filter(|_| state.0.input.is_some() && *state.1 + 1 == state.0.position.len())
+            .and_then(|(row, col, _)| {
+                let value = self.get_value_by_indexes(row - 1, col - 1)?.inner();
+                let lines = value.as_text()?.lock().unwrap().lines().len();
+                value
+                    .is_multiline()
+                    .then_some((*row, lines.max(EDITOR_LINES) + 1))
+            });
+        (1..=self.names.len())
+            .map(|row| match editing {
+                Some((editing, height)) if editing == row => height as u16,
+                _ => 2,
+            })
             .collect()
     }
+    fn choices(
+        &self,
+        state: &DState,
+        current: &Option<(usize, usize, Arc<AtomicUsize>)>,
+        chunks: &[Vec<Rect>],
+    ) -> Option<(Rect, &Vec<String>, usize)> {
+        if state.0.input.is_none() || *state.1 + 1 != state.0.position.len() {
+            return None;
+        }
+        let (row, col, offset) = current.as_ref()?;
+        let value = self.get_value_by_indexes(row - 1, col - 1)?;
+        let cell = chunks
+            .get(*col)?
+            .get(row.checked_sub(offset.load(Ordering::Relaxed))?)?;
+        Some((*cell, value.get_variants()?, value.get_choice_index()?))
+    }
+    fn popup(
+        area: Rect,
+        buf: &mut tui::buffer::Buffer,
+        state: &Context,
+        (cell, variants, selected): (Rect, &Vec<String>, usize),
+    ) {
+        let width = (variants.iter().map(|v| v.len()).max().unwrap_or(0) + 2)
+            .max(cell.width as usize)
+            .min(area.right().saturating_sub(cell.x) as usize) as u16;
+        let height = (variants.len() + 2).min(area.height as usize) as u16;
+        let y = if cell.bottom() + height <= area.bottom() {
+            cell.bottom()
+        } else {
+            cell.y.saturating_sub(height).max(area.y)
+        };
+        let popup = Rect::new(cell.x, y, width, height);
+
+        let list = List::new(
+            variants
+                .iter()
+                .map(|variant| ListItem::new(variant.as_str()))
+                .collect::<Vec<_>>(),
+        )
+        .block(Block::default().borders(Borders::ALL))
+        .style(state.style)
+        .highlight_style(state.highlight_style);
+        let mut list_state = ListState::default();
+        list_state.select(Some(selected));
+
+        Clear.render(popup, buf);
+        StatefulWidget::render(list, popup, buf, &mut list_state);
+    }
     fn chunks(
         area: tui::layout::Rect,
         buf: &mut tui::buffer::Buffer,
-        state: &State,
+        state: &Context,
         names: &Vec<(String, Text<'a>)>,
         values: &[Vec<(String, Text<'a>)>],
+        heights: &[u16],
     ) -> (Vec<Rect>, Vec<Vec<Rect>>) {
-        let widths = {
-            [names]
-                .into_iter()
-                .chain(values.iter())
-                .map(|values| {
-                    let len = values.iter().fold(0usize, |max, (name, value)| {
-                        max.max(name.len() + 1).max(match value {
-                            Text::Text(text) => text
-                                .lock()
-                                .unwrap()
-                                .lines()
-                                .first()
-                                .map_or(0, |str| str.len()),
-                            Text::Span(span) => span.width(),
-                        })
-                    });
-                    if len > 1 {
-                        len + 3
-                    } else {
-                        0
-                    }
-                })
-                .collect::<Vec<usize>>()
-        };
+        let widths = Self::widths(names, values);
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
@@ -242,7 +422,7 @@ impl<'r, 'a> Drawer<'r, 'a> {
                 widths
                     .iter()
                     .map(|width| Constraint::Length(*width as u16))
-                    .chain([Constraint::Min(2)].into_iter())
+                    .chain([Constraint::Min(2)])
                     .collect::<Vec<Constraint>>(),
             )
             .split(inner_area)
@@ -251,11 +431,10 @@ impl<'r, 'a> Drawer<'r, 'a> {
                 Layout::default()
                     .direction(Direction::Vertical)
                     .constraints(
-                        names
-                            .iter()
-                            .enumerate()
-                            .map(|(index, _)| Constraint::Length(if index > 0 { 2 } else { 1 }))
-                            .chain([Constraint::Min(2)].into_iter())
+                        [1].iter()
+                            .chain(heights)
+                            .map(|height| Constraint::Length(*height))
+                            .chain([Constraint::Min(2)])
                             .collect::<Vec<Constraint>>(),
                     )
                     .split(rect)
@@ -275,35 +454,53 @@ impl<'r> DrawerRef for Drawer<'r, '_> {
         if *state.1 > state.0.position.len() {
             return 0;
         }
-        let max_rows = area.height.saturating_sub(1) as usize / 2;
         let current = self.current(&state);
-        if let Some((row, _, c_offset)) = current.as_ref() {
-            let offset = c_offset.load(Ordering::Relaxed);
-            if *row > max_rows + offset {
-                c_offset.fetch_add(1, Ordering::Relaxed);
-            } else if *row <= offset {
-                c_offset.fetch_sub(1, Ordering::Relaxed);
-            }
+        let access = self.access(&state, &current);
+        if *state.1 < state.0.first_level {
+            return self.branch(current).map_or(0, |branch| {
+                state.0.access.set(access);
+                *state.1 += 1;
+                branch.render(area, buf, state)
+            });
         }
+
+        let heights = self.heights(&state, &current);
+        let offset = current.as_ref().map_or(0, |(row, _, c_offset)| {
+            let mut offset = c_offset.load(Ordering::Relaxed).min(row - 1);
+            while offset + 1 < *row
+                && heights[offset..*row]
+                    .iter()
+                    .map(|h| *h as usize)
+                    .sum::<usize>()
+                    > area.height.saturating_sub(1) as usize
+            {
+                offset += 1;
+            }
+            c_offset.store(offset, Ordering::Relaxed);
+            offset
+        });
         let chunks = {
-            let values = self.values();
-            let names = self.names();
+            let values = self.values(&state);
+            let names = self.names(&state);
 
-            let (chunks, inner_chunks) = Self::chunks(area, buf, state.0, &names, &values);
+            let (chunks, inner_chunks) =
+                Self::chunks(area, buf, state.0, &names, &values, &heights[offset..]);
+            let choices = self.choices(&state, &current, &inner_chunks);
 
             [names]
                 .into_iter()
-                .chain(values.into_iter())
+                .chain(values)
                 .enumerate()
                 .map(|(col_index, values)| ToRenderImpl {
                     current: &current,
                     state: &state,
                     col_index,
                     values: Some(values),
+                    accesses: self.accesses(&state, col_index),
                 })
                 .map(Vec::from)
-                .zip(inner_chunks.into_iter())
-                .flat_map(|(paragraphes, chunks)| paragraphes.into_iter().zip(chunks.into_iter()))
+                .zip(inner_chunks)
+                .flat_map(|(paragraphes, chunks)| paragraphes.into_iter().zip(chunks))
                 .for_each(|(paragraph, area)| match paragraph {
                     ToRender::Text(text) => {
                         text.lock().unwrap().widget().render(area, buf);
@@ -311,22 +508,32 @@ impl<'r> DrawerRef for Drawer<'r, '_> {
                     ToRender::Paragraph(paragraph) => paragraph.render(area, buf),
                 });
 
+            if let Some(choices) = choices {
+                Self::popup(area, buf, state.0, choices);
+            }
+
             chunks
         };
 
-        if let Some(branch) = current.and_then(|(name, col, _)| {
-            self.get_value_by_indexes(name - 1, col - 1)
-                .and_then(|value| match &value.1 {
-                    ValueVariant::Struct(b) => Some(b),
-                    _ => None,
-                })
-        }) {
+        if let Some(branch) = self.branch(current) {
+            state.0.access.set(access);
             *state.1 += 1;
             chunks[0].width + branch.render(chunks[1], buf, state)
         } else {
             chunks[0].width
         }
     }
+
+    fn measure(&self, state: DState<'_, '_>, widths: &mut Vec<u16>) {
+        if *state.1 > state.0.position.len() {
+            return;
+        }
+        widths.push(self.width(&state));
+        if let Some(branch) = self.branch(self.current(&state)) {
+            *state.1 += 1;
+            branch.measure(state, widths)
+        }
+    }
 }
 
 impl<'a> Deref for Drawer<'_, 'a> {
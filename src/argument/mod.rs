@@ -1,8 +1,8 @@
-use crate::{state::State, widget::DrawerRef};
+use crate::widget::{Context, DrawerRef};
 use std::collections::HashMap;
 use tui::text::Span;
 
-use value::Value;
+use value::{Access, Value};
 mod drawer;
 pub mod value;
 
@@ -11,6 +11,8 @@ pub struct Args<'a> {
     names: Vec<String>,
     columns: Vec<String>,
     values: HashMap<(usize, usize), Value<'a>>,
+    access: Access,
+    renamable: bool,
 }
 impl<'a> Args<'a> {
     pub fn names<T: IntoIterator>(mut self, names: T) -> Self
@@ -41,6 +43,50 @@ impl<'a> Args<'a> {
         self
     }
 
+    pub fn read_only(mut self) -> Self {
+        self.access = self.access.max(Access::ReadOnly);
+        self
+    }
+    pub fn disabled(mut self) -> Self {
+        self.access = Access::Disabled;
+        self
+    }
+    pub fn get_access(&self) -> Access {
+        self.access
+    }
+
+    pub fn renamable(mut self) -> Self {
+        self.renamable = true;
+        self
+    }
+    pub fn is_renamable(&self) -> bool {
+        self.renamable
+    }
+
+    pub(crate) fn same_shape(&self, other: &Args) -> bool {
+        self.names == other.names
+            && self.columns == other.columns
+            && self.values.len() == other.values.len()
+            && self.values.iter().all(|(indexes, value)| {
+                other
+                    .values
+                    .get(indexes)
+                    .map_or(false, |other| value.same_type(other))
+            })
+    }
+
+    pub(crate) fn same_content(&self, other: &Args) -> bool {
+        self.names == other.names
+            && self.columns == other.columns
+            && self.values.len() == other.values.len()
+            && self.values.iter().all(|(indexes, value)| {
+                other
+                    .values
+                    .get(indexes)
+                    .map_or(false, |other| value.same_content(other))
+            })
+    }
+
     pub fn get_names_raw(&self) -> &Vec<String> {
         &self.names
     }
@@ -88,6 +134,17 @@ impl<'a> Args<'a> {
             .and_then(|index| self.values.get_mut(&(index, column)))
     }
 
+    pub(crate) fn rename(&mut self, old: &str, new: &str) -> bool {
+        if old != new && self.names.iter().any(|name| name == new) {
+            return false;
+        }
+        self.names
+            .iter_mut()
+            .find(|name| *name == old)
+            .map(|name| *name = new.to_string())
+            .is_some()
+    }
+
     fn position<C: Iterator, P>(mut container: C, value: P) -> Option<usize>
     where
         C::Item: PartialEq<P>,
@@ -103,10 +160,14 @@ impl DrawerRef for Args<'_> {
         &self,
         area: tui::layout::Rect,
         buf: &mut tui::buffer::Buffer,
-        state: (&State, &mut usize),
+        state: (&Context, &mut usize),
     ) -> u16 {
         drawer::Drawer(self).render(area, buf, state)
     }
+
+    fn measure(&self, state: (&Context, &mut usize), widths: &mut Vec<u16>) {
+        drawer::Drawer(self).measure(state, widths)
+    }
 }
 
 #[cfg(test)]
@@ -115,6 +176,7 @@ mod tests {
     use tui::text::Span;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison, clippy::redundant_pattern_matching)]
     fn it_works() {
         let args = Args::default()
             .names(["Name 1", "Name 2"])
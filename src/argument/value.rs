@@ -1,4 +1,5 @@
-use crate::{array::Array, Args, Branch, Tree};
+use crate::{array::Array, map::Map, Args, Branch, Tree};
+use linked_hash_map::LinkedHashMap;
 use std::{
     fmt::{Debug, Display},
     str::FromStr,
@@ -6,7 +7,7 @@ use std::{
 };
 use tui_textarea::TextArea;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NumberType {
     U8,
     I8,
@@ -33,8 +34,8 @@ impl Display for NumberType {
                 NumberType::I16 => "i16",
                 NumberType::U32 => "u32",
                 NumberType::I32 => "i32",
-                NumberType::U64 => "u32",
-                NumberType::I64 => "i32",
+                NumberType::U64 => "u64",
+                NumberType::I64 => "i64",
                 NumberType::F32 => "f32",
                 NumberType::F64 => "f64",
                 NumberType::Usize => "usize",
@@ -44,10 +45,11 @@ impl Display for NumberType {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StringType {
     Char,
     String,
+    Text,
 }
 impl Display for StringType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -57,19 +59,23 @@ impl Display for StringType {
             match self {
                 StringType::Char => "Symbol",
                 StringType::String => "String",
+                StringType::Text => "Text",
             }
         )
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Type {
     None,
     Bool,
     Number(NumberType),
     String(StringType),
     Array(Box<Type>),
+    Map(Box<Type>),
     Struct,
+    Enum(Vec<String>),
+    Option(Box<Type>),
 }
 impl Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -82,16 +88,42 @@ impl Display for Type {
                 Type::Number(ty) => ty.to_string(),
                 Type::String(ty) => ty.to_string(),
                 Type::Array(ty) => format!("Array<{}>", ty.as_ref()),
+                Type::Map(ty) => format!("Map<{}>", ty.as_ref()),
                 Type::Struct => "Struct".to_string(),
+                Type::Enum(_) => "Enum".to_string(),
+                Type::Option(ty) => format!("Option<{}>", ty.as_ref()),
             }
         )
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueError {
+    Mismatch { expected: Type, found: Type },
+    Invalid { ty: Type, text: String },
+    Missing(String),
+    Branch,
+}
+impl Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueError::Mismatch { expected, found } => {
+                write!(f, "Expected {expected} value, found {found}")
+            }
+            ValueError::Invalid { ty, text } => write!(f, "\"{text}\" is not a valid {ty} value"),
+            ValueError::Missing(name) => write!(f, "Missing value \"{name}\""),
+            ValueError::Branch => write!(f, "Unexpected branch layout"),
+        }
+    }
+}
+impl std::error::Error for ValueError {}
+
 pub enum ValueVariant<'a> {
     Bool(bool),
     TextArea(Arc<Mutex<TextArea<'a>>>),
     Struct(Branch<'a>),
+    Enum(usize),
+    Option(Box<Value<'a>>, Option<Box<Value<'a>>>),
 }
 impl Debug for ValueVariant<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -102,6 +134,8 @@ impl Debug for ValueVariant<'_> {
                 .field(&arg0.lock().unwrap().lines()[0])
                 .finish(),
             Self::Struct(arg0) => f.debug_tuple("Struct").field(arg0).finish(),
+            Self::Enum(arg0) => f.debug_tuple("Enum").field(arg0).finish(),
+            Self::Option(_, arg1) => f.debug_tuple("Option").field(arg1).finish(),
         }
     }
 }
@@ -116,12 +150,15 @@ impl<'a> Display for ValueVariant<'a> {
                     .lock()
                     .unwrap()
                     .lines()
-                    .get(0)
+                    .first()
                     .unwrap_or(&String::default())
                     .clone(),
-                ValueVariant::Struct(arr @ Branch::Array(_)) =>
+                ValueVariant::Struct(arr @ (Branch::Array(_) | Branch::Map(_))) =>
                     format!("Count: {}", arr.get_list().len()),
                 ValueVariant::Struct(_) => "->".to_string(),
+                ValueVariant::Enum(index) => format!("#{index}"),
+                ValueVariant::Option(_, None) => "<unset>".to_string(),
+                ValueVariant::Option(_, Some(value)) => value.to_string(),
             }
         )
     }
@@ -134,6 +171,8 @@ impl Clone for ValueVariant<'_> {
                 Self::TextArea(Arc::new(Mutex::new(arg0.lock().unwrap().clone())))
             }
             Self::Struct(arg0) => Self::Struct(arg0.clone()),
+            Self::Enum(arg0) => Self::Enum(*arg0),
+            Self::Option(arg0, arg1) => Self::Option(arg0.clone(), arg1.clone()),
         }
     }
 }
@@ -143,21 +182,202 @@ impl<'a> From<TextArea<'a>> for ValueVariant<'a> {
     }
 }
 
+type Check = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+#[derive(Clone)]
+pub enum Rule {
+    Min(f64),
+    Max(f64),
+    Step(f64),
+    MaxLength(usize),
+    Chars(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+    Custom(Check),
+}
+impl Debug for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Min(arg0) => f.debug_tuple("Min").field(arg0).finish(),
+            Self::Max(arg0) => f.debug_tuple("Max").field(arg0).finish(),
+            Self::Step(arg0) => f.debug_tuple("Step").field(arg0).finish(),
+            Self::MaxLength(arg0) => f.debug_tuple("MaxLength").field(arg0).finish(),
+            Self::Chars(arg0) => f.debug_tuple("Chars").field(arg0).finish(),
+            #[cfg(feature = "regex")]
+            Self::Regex(arg0) => f.debug_tuple("Regex").field(arg0).finish(),
+            Self::Custom(_) => f.debug_tuple("Custom").finish(),
+        }
+    }
+}
+impl Rule {
+    pub fn custom(check: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(check))
+    }
+
+    pub fn check(&self, text: &str) -> Result<(), String> {
+        self.check_from(text, 0.0)
+    }
+    pub(crate) fn check_from(&self, text: &str, origin: f64) -> Result<(), String> {
+        let number = || {
+            text.trim()
+                .parse::<f64>()
+                .map_err(|_| "Expected a number".to_string())
+        };
+        match self {
+            Rule::Min(min) => (number()? >= *min)
+                .then_some(())
+                .ok_or_else(|| format!("Must be >= {min}")),
+            Rule::Max(max) => (number()? <= *max)
+                .then_some(())
+                .ok_or_else(|| format!("Must be <= {max}")),
+            Rule::Step(step) if *step <= 0.0 || !step.is_finite() => Ok(()),
+            Rule::Step(step) => {
+                let ratio = (number()? - origin) / step;
+                ((ratio - ratio.round()).abs() < 1e-9)
+                    .then_some(())
+                    .ok_or_else(|| format!("Must be a multiple of {step}"))
+            }
+            Rule::MaxLength(len) => (text.chars().count() <= *len)
+                .then_some(())
+                .ok_or_else(|| format!("At most {len} characters")),
+            Rule::Chars(chars) => text
+                .chars()
+                .find(|sym| !chars.contains(*sym))
+                .map_or(Ok(()), |sym| Err(format!("Invalid character '{sym}'"))),
+            #[cfg(feature = "regex")]
+            Rule::Regex(regex) => regex
+                .is_match(text)
+                .then_some(())
+                .ok_or_else(|| format!("Must match {regex}")),
+            Rule::Custom(check) => check(text),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+    #[default]
+    Editable,
+    ReadOnly,
+    Disabled,
+}
+impl Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Access::Editable => "Editable",
+                Access::ReadOnly => "Read-only",
+                Access::Disabled => "Disabled",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Props {
+    rules: Vec<Rule>,
+    access: Access,
+}
+
 #[derive(Debug, Clone)]
-pub struct Value<'a>(pub(super) Type, pub(super) ValueVariant<'a>);
+pub struct Value<'a>(
+    pub(super) Type,
+    pub(super) ValueVariant<'a>,
+    pub(super) Props,
+);
 impl<'a> Default for Value<'a> {
     fn default() -> Self {
         Self(
             Type::None,
             ValueVariant::TextArea(Arc::new(Mutex::new(TextArea::default()))),
+            Default::default(),
         )
     }
 }
+impl Display for Value<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.as_choice() {
+            Some(choice) => write!(f, "{choice}"),
+            None => write!(f, "{}", self.1),
+        }
+    }
+}
 impl<'a> Value<'a> {
+    pub fn choice<V: ToString>(variants: impl IntoIterator<Item = V>, selected: usize) -> Self {
+        let variants: Vec<String> = variants.into_iter().map(|v| v.to_string()).collect();
+        let selected = selected.min(variants.len().saturating_sub(1));
+        Self(
+            Type::Enum(variants),
+            ValueVariant::Enum(selected),
+            Default::default(),
+        )
+    }
+
+    pub fn multiline(text: impl ToString) -> Self {
+        Self(
+            Type::String(StringType::Text),
+            TextArea::new(
+                text.to_string()
+                    .split('\n')
+                    .map(ToString::to_string)
+                    .collect(),
+            )
+            .into(),
+            Default::default(),
+        )
+        .setup()
+    }
+
     pub fn get_type(&self) -> &Type {
         &self.0
     }
 
+    pub fn rule(mut self, rule: Rule) -> Self {
+        if let ValueVariant::Option(def, value) = self.1 {
+            self.1 = ValueVariant::Option(
+                Box::new(def.rule(rule.clone())),
+                value.map(|value| Box::new(value.rule(rule))),
+            );
+        } else {
+            self.2.rules.push(rule);
+        }
+        self
+    }
+    pub fn min_len(self, len: usize) -> Self {
+        self.limit_len(|array| array.min_len = len)
+    }
+    pub fn max_len(self, len: usize) -> Self {
+        self.limit_len(|array| array.max_len = Some(len))
+    }
+    fn limit_len(mut self, limit: impl Fn(&mut Array<'a>)) -> Self {
+        if let ValueVariant::Option(def, value) = &mut self.1 {
+            [Some(def), value.as_mut()]
+                .into_iter()
+                .flatten()
+                .filter_map(|value| value.as_array_mut())
+                .for_each(&limit);
+        } else if let Some(array) = self.as_array_mut() {
+            limit(array);
+        }
+        self
+    }
+    pub fn get_rules(&self) -> &Vec<Rule> {
+        &self.2.rules
+    }
+    pub fn read_only(mut self) -> Self {
+        self.2.access = self.2.access.max(Access::ReadOnly);
+        self
+    }
+    pub fn disabled(mut self) -> Self {
+        self.2.access = Access::Disabled;
+        self
+    }
+    pub fn get_access(&self) -> Access {
+        self.2.access
+    }
+
     pub fn is_none(&self) -> bool {
         matches!(self.0, Type::None)
     }
@@ -170,12 +390,74 @@ impl<'a> Value<'a> {
     pub fn is_string(&self) -> bool {
         matches!(self.0, Type::String(_))
     }
+    pub fn is_multiline(&self) -> bool {
+        matches!(self.0, Type::String(StringType::Text))
+    }
     pub fn is_array(&self) -> bool {
         matches!(self.0, Type::Array(_))
     }
+    pub fn is_map(&self) -> bool {
+        matches!(self.0, Type::Map(_))
+    }
     pub fn is_struct(&self) -> bool {
         matches!(self.0, Type::Struct)
     }
+    pub fn is_enum(&self) -> bool {
+        matches!(self.0, Type::Enum(_))
+    }
+    pub fn is_option(&self) -> bool {
+        matches!(self.0, Type::Option(_))
+    }
+    pub fn is_unset(&self) -> bool {
+        matches!(self.1, ValueVariant::Option(_, None))
+    }
+
+    pub fn inner(&self) -> &Self {
+        if let ValueVariant::Option(_, Some(value)) = &self.1 {
+            value
+        } else {
+            self
+        }
+    }
+    pub fn inner_mut(&mut self) -> &mut Self {
+        if !matches!(self.1, ValueVariant::Option(_, Some(_))) {
+            return self;
+        }
+        match &mut self.1 {
+            ValueVariant::Option(_, Some(value)) => value,
+            _ => unreachable!(),
+        }
+    }
+    pub fn assign_default(&mut self) -> bool {
+        if let ValueVariant::Option(def, value @ None) = &mut self.1 {
+            *value = Some(def.clone());
+            true
+        } else {
+            false
+        }
+    }
+    pub fn clear(&mut self) -> bool {
+        if let ValueVariant::Option(_, value @ Some(_)) = &mut self.1 {
+            *value = None;
+            true
+        } else {
+            false
+        }
+    }
+    pub(crate) fn cleared(&self) -> Result<Self, String> {
+        let mut value = self.clone();
+        match &mut value.1 {
+            ValueVariant::Option(_, inner) => *inner = None,
+            ValueVariant::Bool(flag) => *flag = false,
+            ValueVariant::Enum(index) => *index = 0,
+            ValueVariant::TextArea(_) => value.set_text(if self.is_number() { "0" } else { "" }),
+            ValueVariant::Struct(
+                Branch::Array(Array { tree, .. }) | Branch::Map(Map { tree, .. }),
+            ) => tree.get_branches_mut().clear(),
+            ValueVariant::Struct(_) => return Err("Not clearable".into()),
+        }
+        value.validate().map(|_| value)
+    }
 
     pub fn as_bool(&self) -> Option<&bool> {
         if let ValueVariant::Bool(value) = &self.1 {
@@ -212,7 +494,46 @@ impl<'a> Value<'a> {
             None
         }
     }
-    #[allow(unused)]
+    pub fn as_choice(&self) -> Option<&String> {
+        self.get_choice_index()
+            .and_then(|index| self.get_variants()?.get(index))
+    }
+    pub fn get_choice_index(&self) -> Option<usize> {
+        if let ValueVariant::Enum(index) = &self.1 {
+            Some(*index)
+        } else {
+            None
+        }
+    }
+    pub fn get_variants(&self) -> Option<&Vec<String>> {
+        if let Type::Enum(variants) = &self.0 {
+            Some(variants)
+        } else {
+            None
+        }
+    }
+    pub fn select(&mut self, choice: &str) -> bool {
+        let Some(index) = self
+            .get_variants()
+            .and_then(|variants| variants.iter().position(|v| v == choice))
+        else {
+            return false;
+        };
+        self.1 = ValueVariant::Enum(index);
+        true
+    }
+    pub(crate) fn cycle(&mut self, is_next: bool) {
+        let len = self.get_variants().map_or(0, Vec::len);
+        if let ValueVariant::Enum(index) = &mut self.1 {
+            if len > 0 {
+                *index = if is_next {
+                    (*index + 1) % len
+                } else {
+                    (*index + len - 1) % len
+                };
+            }
+        }
+    }
     pub fn as_array(&self) -> Option<&Array<'a>> {
         if let ValueVariant::Struct(Branch::Array(tree)) = &self.1 {
             Some(tree)
@@ -228,9 +549,38 @@ impl<'a> Value<'a> {
         }
     }
 
+    pub fn as_map(&self) -> Option<&Map<'a>> {
+        if let ValueVariant::Struct(Branch::Map(map)) = &self.1 {
+            Some(map)
+        } else {
+            None
+        }
+    }
+    pub(crate) fn as_map_mut(&mut self) -> Option<&mut Map<'a>> {
+        if let ValueVariant::Struct(Branch::Map(map)) = &mut self.1 {
+            Some(map)
+        } else {
+            None
+        }
+    }
+
     pub fn into_array(mut self) -> Self {
         self.1 = ValueVariant::Struct(Array::new(self.clone().into()).into());
         self.0 = Type::Array(Box::new(self.0));
+        self.2 = Default::default();
+        self
+    }
+    pub fn into_map(mut self) -> Self {
+        self.1 = ValueVariant::Struct(Map::new(self.clone().into()).into());
+        self.0 = Type::Map(Box::new(self.0));
+        self.2 = Default::default();
+        self
+    }
+    pub fn into_option(mut self) -> Self {
+        let value = Box::new(self.clone());
+        self.1 = ValueVariant::Option(value.clone(), Some(value));
+        self.0 = Type::Option(Box::new(self.0));
+        self.2 = Default::default();
         self
     }
 
@@ -239,12 +589,127 @@ impl<'a> Value<'a> {
             text.lock()
                 .unwrap()
                 .lines()
-                .get(0)
+                .first()
                 .and_then(|str| str.parse::<T>().ok())
         })
     }
 
+    pub fn get<T: FromValue>(&self) -> Result<T, ValueError> {
+        T::from_value(self)
+    }
+
+    fn expect(&self, expected: Type) -> Result<(), ValueError> {
+        if self.0 == expected || expected == Type::String(StringType::String) && self.is_multiline()
+        {
+            Ok(())
+        } else {
+            Err(ValueError::Mismatch {
+                expected,
+                found: self.0.clone(),
+            })
+        }
+    }
+
+    pub(crate) fn text(&self) -> Option<String> {
+        self.as_text()
+            .map(|text| text.lock().unwrap().lines().join("\n"))
+            .or_else(|| self.as_choice().cloned())
+    }
+
+    pub(crate) fn same_type(&self, other: &Value) -> bool {
+        self.0 == other.0
+            && match (self.as_struct(), other.as_struct()) {
+                (Some(l), Some(r)) => l.same_shape(r),
+                (l, r) => l.is_none() && r.is_none(),
+            }
+    }
+
+    pub(crate) fn same_content(&self, other: &Value) -> bool {
+        self.0 == other.0
+            && match (&self.1, &other.1) {
+                (ValueVariant::Bool(l), ValueVariant::Bool(r)) => l == r,
+                (ValueVariant::TextArea(l), ValueVariant::TextArea(r)) => {
+                    Arc::as_ptr(l).cast::<()>() == Arc::as_ptr(r).cast::<()>()
+                        || l.lock().unwrap().lines() == r.lock().unwrap().lines()
+                }
+                (ValueVariant::Struct(l), ValueVariant::Struct(r)) => l.same_content(r),
+                (ValueVariant::Enum(l), ValueVariant::Enum(r)) => l == r,
+                (ValueVariant::Option(_, l), ValueVariant::Option(_, r)) => match (l, r) {
+                    (Some(l), Some(r)) => l.same_content(r),
+                    (l, r) => l.is_none() && r.is_none(),
+                },
+                _ => false,
+            }
+    }
+
+    pub(crate) fn assign(&mut self, other: &Value<'a>) -> bool {
+        if self.same_type(other) {
+            let limits = self.as_array().map(|array| (array.min_len, array.max_len));
+            self.1 = other.1.clone();
+            if let (Some((min_len, max_len)), Some(array)) = (limits, self.as_array_mut()) {
+                array.min_len = min_len;
+                array.max_len = max_len;
+            }
+            return true;
+        }
+        if let ValueVariant::Option(def, value) = &mut self.1 {
+            if other.is_unset() || !def.same_type(other.inner()) {
+                return false;
+            }
+            let mut new = def.as_ref().clone();
+            new.1 = other.inner().1.clone();
+            *value = Some(Box::new(new));
+            return true;
+        }
+        if let ValueVariant::Option(_, Some(other)) = &other.1 {
+            if self.same_type(other) {
+                self.1 = other.1.clone();
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let ValueVariant::Option(_, value) = &self.1 {
+            return value.as_ref().map_or(Ok(()), |value| value.validate());
+        }
+        if let Some(array) = self.as_array() {
+            return array.validate();
+        }
+        if let Some(map) = self.as_map() {
+            return map.validate();
+        }
+        let Some(text) = self.text() else {
+            return Ok(());
+        };
+        if !self.check_type() {
+            return Err(format!("Expected {}", self.0));
+        }
+        let origin = self
+            .2
+            .rules
+            .iter()
+            .find_map(|rule| match rule {
+                Rule::Min(min) => Some(*min),
+                _ => None,
+            })
+            .unwrap_or_default();
+        self.2
+            .rules
+            .iter()
+            .try_for_each(|rule| rule.check_from(&text, origin))
+    }
+
     pub fn check(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    pub(crate) fn title(&self) -> String {
+        self.validate().err().unwrap_or_else(|| self.0.to_string())
+    }
+
+    fn check_type(&self) -> bool {
         if self.as_text().is_some() {
             match &self.0 {
                 Type::Number(ty) => match ty {
@@ -264,11 +729,27 @@ impl<'a> Value<'a> {
                 Type::String(_) => self.parse::<String>().is_some(),
                 _ => true,
             }
+        } else if let Some(index) = self.get_choice_index() {
+            self.get_variants()
+                .map_or(false, |variants| index < variants.len())
         } else {
             true
         }
     }
 
+    pub(crate) fn set_text(&mut self, text: &str) {
+        if let ValueVariant::Option(_, Some(value)) = &mut self.1 {
+            value.set_text(text);
+        } else if self.is_enum() {
+            self.select(text);
+        } else if let ValueVariant::TextArea(area) = &self.1 {
+            let mut area = area.lock().unwrap();
+            *area = TextArea::new(text.split('\n').map(ToString::to_string).collect());
+            area.set_max_histories(1);
+            area.move_cursor(tui_textarea::CursorMove::End);
+        }
+    }
+
     fn setup(self) -> Self {
         if let ValueVariant::TextArea(text) = &self.1 {
             let mut text = text.lock().unwrap();
@@ -297,6 +778,7 @@ macro_rules! impl_from_for_value {
                 Self(
                     <$ty as GetType>::get(),
                     $var,
+                    Default::default(),
                 )
                 .setup()
             }
@@ -304,7 +786,7 @@ macro_rules! impl_from_for_value {
     };
 }
 
-trait GetType {
+pub trait GetType {
     fn get() -> Type;
 }
 impl_get_type!(bool => Type::Bool);
@@ -323,12 +805,46 @@ impl_get_type!(isize => Type::Number(NumberType::Isize));
 impl_get_type!(char => Type::String(StringType::Char));
 impl_get_type!(&str, String => Type::String(StringType::String));
 impl_get_type!(Tree<'_>, Args<'_> => Type::Struct);
+impl<T: GetType> GetType for Vec<T> {
+    fn get() -> Type {
+        Type::Array(T::get().into())
+    }
+}
+impl<T: GetType> GetType for Option<T> {
+    fn get() -> Type {
+        Type::Option(T::get().into())
+    }
+}
+impl<T: GetType> GetType for LinkedHashMap<String, T> {
+    fn get() -> Type {
+        Type::Map(T::get().into())
+    }
+}
+impl<T: GetType, const S: usize> GetType for [T; S] {
+    fn get() -> Type {
+        Type::Array(T::get().into())
+    }
+}
 
 impl_from_for_value!(bool => ValueVariant::Bool(v); v);
 impl_from_for_value!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64, usize, isize, String, &str, char
-        => TextArea::new(vec![v].iter().map(ToString::to_string).collect()).into(); v);
+        => TextArea::new([v].iter().map(ToString::to_string).collect()).into(); v);
 impl_from_for_value!(Tree<'a>, Args<'a>, => ValueVariant::Struct(v.into()); v);
 
+impl<'a> From<Branch<'a>> for Value<'a> {
+    fn from(branch: Branch<'a>) -> Self {
+        Self(
+            match &branch {
+                Branch::Array(array) => Type::Array(array.def.get_type().clone().into()),
+                Branch::Map(map) => Type::Map(map.def.get_type().clone().into()),
+                Branch::Args(_) | Branch::Tree(_) => Type::Struct,
+            },
+            ValueVariant::Struct(branch),
+            Default::default(),
+        )
+    }
+}
+
 impl<T: GetType + Default> From<Vec<T>> for Value<'_>
 where
     Self: From<T>,
@@ -348,6 +864,45 @@ where
                     )
                     .into(),
             ),
+            Default::default(),
+        )
+    }
+}
+impl<T: GetType + Default> From<LinkedHashMap<String, T>> for Value<'_>
+where
+    Self: From<T>,
+{
+    fn from(value: LinkedHashMap<String, T>) -> Self {
+        Self(
+            Type::Map(<T as GetType>::get().into()),
+            ValueVariant::Struct(
+                value
+                    .into_iter()
+                    .fold(
+                        Map::new(Self::from(T::default()).into()),
+                        |mut map, (key, v)| {
+                            map.insert(key, v);
+                            map
+                        },
+                    )
+                    .into(),
+            ),
+            Default::default(),
+        )
+    }
+}
+impl<'a, T: GetType + Default> From<Option<T>> for Value<'a>
+where
+    Self: From<T>,
+{
+    fn from(value: Option<T>) -> Self {
+        Self(
+            <Option<T> as GetType>::get(),
+            ValueVariant::Option(
+                Box::new(Self::from(T::default())),
+                value.map(|value| Box::new(Self::from(value))),
+            ),
+            Default::default(),
         )
     }
 }
@@ -356,6 +911,222 @@ where
     Self: From<T>,
 {
     fn from(value: [T; S]) -> Self {
-        value.into_iter().collect::<Vec<_>>().into()
+        <Self as From<Vec<T>>>::from(value.into_iter().collect())
+            .min_len(S)
+            .max_len(S)
+    }
+}
+
+pub trait FromValue: GetType + Sized {
+    fn from_value(value: &Value) -> Result<Self, ValueError>;
+}
+
+macro_rules! impl_from_value {
+    ($($ty:ty $(,)?)+) => {
+        $(impl FromValue for $ty {
+            fn from_value(value: &Value) -> Result<Self, ValueError> {
+                value.expect(<$ty as GetType>::get())?;
+                let text = value.text().unwrap_or_default();
+                text.parse().map_err(|_| ValueError::Invalid {
+                    ty: value.0.clone(),
+                    text,
+                })
+            }
+        })+
+        impl_from_value!(@try_from $($ty,)+ bool);
+    };
+    (@try_from $($ty:ty $(,)?)+) => {
+        $(impl TryFrom<&Value<'_>> for $ty {
+            type Error = ValueError;
+
+            fn try_from(value: &Value<'_>) -> Result<Self, Self::Error> {
+                FromValue::from_value(value)
+            }
+        })+
+    };
+}
+
+impl_from_value!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64, usize, isize, String, char);
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, ValueError> {
+        value.expect(Type::Bool)?;
+        value.as_bool().copied().ok_or(ValueError::Mismatch {
+            expected: Type::Bool,
+            found: value.0.clone(),
+        })
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self, ValueError> {
+        value.expect(<Self as GetType>::get())?;
+        let array = value.as_array().ok_or(ValueError::Branch)?;
+        array
+            .get_branches()
+            .iter()
+            .map(|(name, element)| match element {
+                Branch::Args(args) if !array.is_wrapped() => args
+                    .get_value("", "Value")
+                    .ok_or_else(|| ValueError::Missing(name.clone()))
+                    .and_then(T::from_value),
+                element => T::from_value(&element.clone().into()),
+            })
+            .collect()
+    }
+}
+impl<T: FromValue> FromValue for LinkedHashMap<String, T> {
+    fn from_value(value: &Value) -> Result<Self, ValueError> {
+        value.expect(<Self as GetType>::get())?;
+        let map = value.as_map().ok_or(ValueError::Branch)?;
+        map.get_branches()
+            .iter()
+            .map(|(key, entry)| {
+                match entry {
+                    Branch::Args(args) if !map.is_wrapped() => args
+                        .get_value("", "Value")
+                        .ok_or_else(|| ValueError::Missing(key.clone()))
+                        .and_then(T::from_value),
+                    entry => T::from_value(&entry.clone().into()),
+                }
+                .map(|value| (key.clone(), value))
+            })
+            .collect()
+    }
+}
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, ValueError> {
+        value.expect(<Self as GetType>::get())?;
+        match &value.1 {
+            ValueVariant::Option(_, value) => value.as_deref().map(T::from_value).transpose(),
+            _ => Err(ValueError::Branch),
+        }
+    }
+}
+impl<T: FromValue> TryFrom<&Value<'_>> for Option<T> {
+    type Error = ValueError;
+
+    fn try_from(value: &Value<'_>) -> Result<Self, Self::Error> {
+        FromValue::from_value(value)
+    }
+}
+impl<T: FromValue> TryFrom<&Value<'_>> for Vec<T> {
+    type Error = ValueError;
+
+    fn try_from(value: &Value<'_>) -> Result<Self, Self::Error> {
+        FromValue::from_value(value)
+    }
+}
+impl<T: FromValue> TryFrom<&Value<'_>> for LinkedHashMap<String, T> {
+    type Error = ValueError;
+
+    fn try_from(value: &Value<'_>) -> Result<Self, Self::Error> {
+        FromValue::from_value(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NumberType, Rule, Type, Value, ValueError};
+
+    #[test]
+    fn it_works() {
+        assert_eq!(Value::from(42u16).get::<u16>(), Ok(42));
+        assert_eq!(Value::from('x').get::<char>(), Ok('x'));
+        assert_eq!(Value::from(true).get::<bool>(), Ok(true));
+        assert_eq!(u8::try_from(&Value::from(7u8)), Ok(7));
+        assert_eq!(Value::from(vec![1.5f32, 2.5]).get(), Ok(vec![1.5f32, 2.5]));
+        assert_eq!(
+            Value::from(42u16).get::<u8>(),
+            Err(ValueError::Mismatch {
+                expected: Type::Number(NumberType::U8),
+                found: Type::Number(NumberType::U16),
+            })
+        );
+
+        let value = Value::from(1u8);
+        value.as_text().unwrap().lock().unwrap().insert_str("x");
+        assert_eq!(
+            value.get::<u8>(),
+            Err(ValueError::Invalid {
+                ty: Type::Number(NumberType::U8),
+                text: "1x".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn rules() {
+        let mut value = Value::from(20u8)
+            .rule(Rule::Min(10.0))
+            .rule(Rule::Max(100.0))
+            .rule(Rule::Step(5.0));
+        assert_eq!(value.validate(), Ok(()));
+        value.set_text("7");
+        assert_eq!(value.validate(), Err("Must be >= 10".to_string()));
+        value.set_text("22");
+        assert_eq!(value.validate(), Err("Must be a multiple of 5".to_string()));
+        value.set_text("-");
+        assert_eq!(value.validate(), Err("Expected u8".to_string()));
+
+        let mut value = Value::from(1u8).rule(Rule::Min(1.0)).rule(Rule::Step(2.0));
+        assert_eq!(value.validate(), Ok(()));
+        value.set_text("5");
+        assert_eq!(value.validate(), Ok(()));
+        value.set_text("4");
+        assert_eq!(value.validate(), Err("Must be a multiple of 2".to_string()));
+        let value = Value::from(3u8)
+            .rule(Rule::Step(0.0))
+            .rule(Rule::Step(-1.0));
+        assert_eq!(value.validate(), Ok(()));
+
+        let mut value = Value::from("abc")
+            .rule(Rule::MaxLength(4))
+            .rule(Rule::Chars("abcdef".into()))
+            .rule(Rule::custom(|text| {
+                (!text.starts_with('f'))
+                    .then_some(())
+                    .ok_or_else(|| "Must not start with 'f'".to_string())
+            }));
+        assert!(value.check());
+        value.set_text("abcde");
+        assert_eq!(value.validate(), Err("At most 4 characters".to_string()));
+        value.set_text("abx");
+        assert_eq!(value.validate(), Err("Invalid character 'x'".to_string()));
+        value.set_text("fab");
+        assert_eq!(value.validate(), Err("Must not start with 'f'".to_string()));
+    }
+
+    #[test]
+    fn choice() {
+        let mut value = Value::choice([9600, 19200, 115200], 1);
+        assert_eq!(value.to_string(), "19200");
+        value.cycle(true);
+        value.cycle(true);
+        assert_eq!(value.as_choice().map(String::as_str), Some("9600"));
+        assert!(value.select("115200"));
+        assert!(!value.select("57600"));
+        assert_eq!(value.get_choice_index(), Some(2));
+        assert!(value.check());
+    }
+
+    #[test]
+    fn optional() {
+        let mut value = Value::from(None::<u8>);
+        assert!(value.is_unset());
+        assert_eq!(value.to_string(), "<unset>");
+        assert_eq!(value.get::<Option<u8>>(), Ok(None));
+        assert!(value.assign_default());
+        value.set_text("5");
+        assert_eq!(value.get::<Option<u8>>(), Ok(Some(5)));
+        assert!(value.clear());
+        assert_eq!(Value::from(Some(7u8)).get(), Ok(Some(7u8)));
+    }
+
+    #[test]
+    fn multiline() {
+        let value = Value::multiline("first\nsecond");
+        assert_eq!(value.get::<String>(), Ok("first\nsecond".into()));
+        assert_eq!(value.to_string(), "first");
     }
 }
@@ -1,19 +1,70 @@
-use crate::{Args, Tree, Value};
+use crate::{Args, Branch, Tree, Value};
 use std::ops::Deref;
 
 #[derive(Debug, Clone)]
 pub struct Array<'a> {
     pub(crate) def: Box<Value<'a>>,
     pub tree: Tree<'a>,
+    pub(crate) min_len: usize,
+    pub(crate) max_len: Option<usize>,
 }
 impl<'a> Array<'a> {
     pub(crate) fn new(def: Box<Value<'a>>) -> Self {
         Self {
             def,
             tree: Default::default(),
+            min_len: 0,
+            max_len: None,
         }
     }
 
+    pub fn get_min_len(&self) -> usize {
+        self.min_len
+    }
+    pub fn get_max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+    pub fn is_fixed(&self) -> bool {
+        self.max_len == Some(self.min_len)
+    }
+
+    pub(crate) fn check_len(&self, len: usize) -> Result<(), String> {
+        if self.is_fixed() && len != self.min_len {
+            Err(format!("Exactly {} elements", self.min_len))
+        } else if len < self.min_len {
+            Err(format!("At least {} elements", self.min_len))
+        } else if self.max_len.map_or(false, |max| len > max) {
+            Err(format!(
+                "At most {} elements",
+                self.max_len.unwrap_or_default()
+            ))
+        } else {
+            Ok(())
+        }
+    }
+    pub(crate) fn check_insert(&self) -> Result<(), String> {
+        if self.is_fixed() {
+            return Err("Fixed size".into());
+        }
+        self.check_len(self.branches.len() + 1)
+    }
+    pub(crate) fn check_remove(&self) -> Result<(), String> {
+        if self.is_fixed() {
+            return Err("Fixed size".into());
+        }
+        self.check_len(self.branches.len().saturating_sub(1))
+    }
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        self.check_len(self.branches.len())?;
+        let prototype = self.prototype();
+        self.branches
+            .iter()
+            .find(|(_, element)| !element.same_shape(&prototype))
+            .map_or(Ok(()), |(index, _)| {
+                Err(format!("Element {index} has a different layout"))
+            })
+    }
+
     pub fn is_wrapped(&self) -> bool {
         self.def.is_struct()
     }
@@ -23,10 +74,12 @@ impl<'a> Array<'a> {
         self.insert(index, (*self.def).clone())
     }
 
-    pub(crate) fn insert(&mut self, index: Option<usize>, value: impl Into<Value<'a>>) {
-        let len = self.branches.len();
-        let value: Value = value.into();
-        let value = if let Some(b) = value.as_struct() {
+    pub(crate) fn prototype(&self) -> Branch<'a> {
+        Self::element_of((*self.def).clone())
+    }
+
+    pub(crate) fn element_of(value: Value<'a>) -> Branch<'a> {
+        if let Some(b) = value.as_struct() {
             b.clone()
         } else {
             Args::default()
@@ -34,30 +87,52 @@ impl<'a> Array<'a> {
                 .columns(["Value"])
                 .value("", "Value", value)
                 .into()
-        };
-        if index.filter(|index| *index < len).is_some() {
-            self.tree.branches.insert("".into(), value);
-            self.update();
-        } else {
-            self.tree.branches.insert(len.to_string(), value);
         }
     }
 
+    pub(crate) fn insert(&mut self, index: Option<usize>, value: impl Into<Value<'a>>) {
+        let value = Self::element_of(value.into());
+        let mut elements = self.take();
+        elements.insert(index.unwrap_or(usize::MAX).min(elements.len()), value);
+        self.update(elements);
+    }
+
     pub(crate) fn remove(&mut self, index: &String) -> Option<String> {
         let not_last = self.branches.back().filter(|&(s, _)| s == index).is_none();
         self.tree.branches.remove(index);
-        self.update();
+        let elements = self.take();
+        self.update(elements);
         not_last
             .then_some(index)
             .or_else(|| self.branches.back().map(|(s, _)| s))
             .cloned()
     }
 
-    fn update(&mut self) {
-        self.tree.branches = self
-            .branches
-            .iter()
-            .map(|(_, v)| v.clone())
+    pub(crate) fn move_element(&mut self, from: usize, to: usize) -> bool {
+        let mut elements = self.take();
+        let moved = from < elements.len() && to < elements.len();
+        if moved {
+            let element = elements.remove(from);
+            elements.insert(to, element);
+        }
+        self.update(elements);
+        moved
+    }
+
+    pub(crate) fn element(&self, index: usize) -> Option<&Branch<'a>> {
+        self.branches.get(&index.to_string())
+    }
+
+    fn take(&mut self) -> Vec<Branch<'a>> {
+        std::mem::take(&mut self.tree.branches)
+            .into_iter()
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    fn update(&mut self, elements: Vec<Branch<'a>>) {
+        self.tree.branches = elements
+            .into_iter()
             .enumerate()
             .map(|(i, v)| (i.to_string(), v))
             .collect()
@@ -1,9 +1,54 @@
-use crate::{array::Array, state::State, widget::DrawerRef, Args, Tree};
+use crate::{
+    array::Array,
+    map::Map,
+    widget::{Context, DrawerRef},
+    Access, Args, Tree,
+};
 use linked_hash_map::LinkedHashMap;
 use paste::paste;
 
 pub type Branches<'a> = LinkedHashMap<String, Branch<'a>>;
 
+pub(crate) fn rekey(branches: &mut Branches, old: &str, new: &str) -> bool {
+    if !branches.contains_key(old) || old != new && branches.contains_key(new) {
+        return false;
+    }
+    *branches = std::mem::take(branches)
+        .into_iter()
+        .map(|(name, branch)| {
+            if name == old {
+                (new.to_string(), branch)
+            } else {
+                (name, branch)
+            }
+        })
+        .collect();
+    true
+}
+
+pub(crate) fn insert<'a>(
+    branches: &mut Branches<'a>,
+    index: usize,
+    name: String,
+    branch: Branch<'a>,
+) {
+    let tail = branches.keys().skip(index).cloned().collect::<Vec<_>>();
+    branches.insert(name, branch);
+    tail.iter().for_each(|name| {
+        branches.get_refresh(name);
+    });
+}
+
+pub(crate) fn unique_name(branches: &Branches, base: &str) -> String {
+    (1..)
+        .map(|index| match index {
+            1 => base.to_string(),
+            index => format!("{base} {index}"),
+        })
+        .find(|name| !branches.contains_key(name))
+        .unwrap_or_default()
+}
+
 macro_rules! branch {
     {$($name:ident, $ty:ty => $list:expr )+} => {
         paste! {
@@ -44,12 +89,18 @@ macro_rules! branch {
                     &self,
                     area: tui::layout::Rect,
                     buf: &mut tui::buffer::Buffer,
-                    state: (&State, &mut usize),
+                    state: (&Context, &mut usize),
                 ) -> u16 {
                     match self {$(
                         Self:: [< $name:camel >] ($name) => $name .render(area, buf, state),
                     )+}
                 }
+
+                fn measure(&self, state: (&Context, &mut usize), widths: &mut Vec<u16>) {
+                    match self {$(
+                        Self:: [< $name:camel >] ($name) => $name .measure(state, widths),
+                    )+}
+                }
             }
 
             $(impl<'a> From<$ty> for Branch<'a> {
@@ -61,8 +112,42 @@ macro_rules! branch {
     };
 }
 
+impl Branch<'_> {
+    pub fn get_access(&self) -> Access {
+        match self {
+            Branch::Args(args) => args.get_access(),
+            Branch::Tree(tree)
+            | Branch::Array(Array { tree, .. })
+            | Branch::Map(Map { tree, .. }) => tree.get_access(),
+        }
+    }
+    pub fn same_shape(&self, other: &Branch) -> bool {
+        match (self, other) {
+            (Branch::Args(l), Branch::Args(r)) => l.same_shape(r),
+            (Branch::Tree(l), Branch::Tree(r)) => l.same_shape(r),
+            (Branch::Array(l), Branch::Array(r)) => l.def.same_type(&r.def),
+            (Branch::Map(l), Branch::Map(r)) => l.def.same_type(&r.def),
+            _ => false,
+        }
+    }
+    pub fn same_content(&self, other: &Branch) -> bool {
+        match (self, other) {
+            (Branch::Args(l), Branch::Args(r)) => l.same_content(r),
+            (Branch::Tree(l), Branch::Tree(r)) => l.same_content(r),
+            (Branch::Array(l), Branch::Array(r)) => {
+                l.def.same_type(&r.def) && l.tree.same_content(&r.tree)
+            }
+            (Branch::Map(l), Branch::Map(r)) => {
+                l.def.same_type(&r.def) && l.tree.same_content(&r.tree)
+            }
+            _ => false,
+        }
+    }
+}
+
 branch! {
     args, Args<'a> => args.get_names().into_iter().map(|span| span.content.to_string()).collect()
     tree, Tree<'a> => tree.get_branches().iter().map(|(name, _)| name.clone()).collect()
     array, Array<'a> => array.get_branches().iter().map(|(name, _)| name.clone()).collect()
+    map, Map<'a> => map.get_branches().iter().map(|(name, _)| name.clone()).collect()
 }
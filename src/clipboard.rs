@@ -0,0 +1,21 @@
+use crate::{Branch, Value};
+
+#[derive(Debug, Clone)]
+pub enum Clip<'a> {
+    Value(Value<'a>),
+    Row(Vec<Option<Value<'a>>>),
+    Branch(Branch<'a>),
+}
+impl Clip<'_> {
+    pub fn text(&self) -> Option<String> {
+        match self {
+            Clip::Value(value) => Some(value.text().unwrap_or_else(|| value.to_string())),
+            Clip::Row(_) | Clip::Branch(_) => None,
+        }
+    }
+}
+
+pub trait SystemClipboard: Send + Sync {
+    fn get(&self) -> Option<String>;
+    fn set(&self, text: String);
+}
@@ -0,0 +1,109 @@
+use crate::{array::Array, map::Map, state::Node, Branch, Branches};
+
+pub(crate) fn paths(tabs: &Branches, baseline: &Branches) -> Vec<Vec<Node>> {
+    let mut paths = Vec::new();
+    for (name, branch) in tabs.iter() {
+        let path = vec![Node::tree(name)];
+        match baseline.get(name) {
+            Some(original) => collect(branch, original, path, &mut paths),
+            None => paths.push(path),
+        }
+    }
+    paths
+}
+
+fn collect(branch: &Branch, original: &Branch, path: Vec<Node>, out: &mut Vec<Vec<Node>>) {
+    match (branch, original) {
+        (Branch::Args(args), Branch::Args(original)) => {
+            for (row, name) in args.get_names_raw().iter().enumerate() {
+                for (column, title) in args.get_columns_raw().iter().enumerate() {
+                    let Some(value) = args.get_value_by_indexes(row, column) else {
+                        continue;
+                    };
+                    let mut path = path.clone();
+                    path.push(Node::args(name, column));
+                    let Some(original) = original.get_value(name, title) else {
+                        out.push(path);
+                        continue;
+                    };
+                    if value.same_content(original) {
+                        continue;
+                    }
+                    match (value.inner().as_struct(), original.inner().as_struct()) {
+                        (Some(branch), Some(original)) => collect(branch, original, path, out),
+                        _ => out.push(path),
+                    }
+                }
+            }
+        }
+        (
+            Branch::Tree(tree) | Branch::Array(Array { tree, .. }) | Branch::Map(Map { tree, .. }),
+            Branch::Tree(original)
+            | Branch::Array(Array { tree: original, .. })
+            | Branch::Map(Map { tree: original, .. }),
+        ) => {
+            if tree.get_branches().len() != original.get_branches().len() {
+                out.push(path.clone());
+            }
+            for (name, branch) in tree.get_branches().iter() {
+                let mut path = path.clone();
+                path.push(Node::tree(name));
+                match original.get_branches().get(name) {
+                    Some(original) if branch.same_content(original) => (),
+                    Some(original) => collect(branch, original, path, out),
+                    None => out.push(path),
+                }
+            }
+        }
+        _ => out.push(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Args, Event, Node, Tree, TreeEdit, TreeEditState};
+
+    #[test]
+    fn it_works() {
+        let mut tree_edit = TreeEdit::new("Test").tab(
+            "Network".to_string(),
+            Tree::default().branch(
+                "eth0",
+                Args::default()
+                    .names(["Port", "Routes"])
+                    .columns(["Value"])
+                    .value("Port", "Value", 80u16)
+                    .value("Routes", "Value", vec![1u8]),
+            ),
+        );
+        let mut state = TreeEditState::default();
+        let port = vec![
+            Node::tree("Network"),
+            Node::tree("eth0"),
+            Node::args("Port", 0),
+        ];
+        assert!(!tree_edit.is_dirty());
+
+        state.transition(Event::NextLevel, &mut tree_edit);
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::Char('8'), &mut tree_edit);
+        state.transition(Event::Enter, &mut tree_edit);
+        assert_eq!(tree_edit.dirty_paths(), vec![port.clone()]);
+
+        tree_edit
+            .set_at("Network/eth0/Routes", vec![1u8, 2])
+            .unwrap();
+        assert_eq!(tree_edit.dirty_paths().len(), 3);
+        tree_edit.reset_at("Network/eth0/Routes").unwrap();
+        assert_eq!(tree_edit.dirty_paths(), vec![port]);
+
+        tree_edit.accept();
+        assert!(!tree_edit.is_dirty());
+        tree_edit.set_at("Network/eth0/Port", 22u16).unwrap();
+        tree_edit.reset_all();
+        assert_eq!(
+            tree_edit.get_at("Network/eth0/Port").unwrap().get(),
+            Ok(808u16)
+        );
+    }
+}
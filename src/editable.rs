@@ -0,0 +1,76 @@
+use crate::{Branch, ValueError};
+
+#[cfg(feature = "derive")]
+pub use tui_va_tree_edit_derive::TreeEditable;
+
+pub trait TreeEditable: Sized {
+    fn to_branch<'a>(&self) -> Branch<'a>;
+    fn from_branch(branch: &Branch) -> Result<Self, ValueError>;
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use crate::TreeEditable;
+
+    #[derive(Debug, Default, Clone, PartialEq, TreeEditable)]
+    struct Port {
+        number: u16,
+        #[tree_edit(rename = "Open")]
+        open: bool,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, TreeEditable)]
+    struct Device {
+        name: String,
+        gain: f32,
+        main: Port,
+        ports: Vec<Port>,
+        ids: Vec<u8>,
+        #[tree_edit(skip)]
+        cache: usize,
+    }
+
+    #[test]
+    fn it_works() {
+        let device = Device {
+            name: "Sensor".into(),
+            gain: 0.5,
+            main: Port {
+                number: 22,
+                open: true,
+            },
+            ports: vec![
+                Port {
+                    number: 80,
+                    open: false,
+                },
+                Port {
+                    number: 443,
+                    open: true,
+                },
+            ],
+            ids: vec![1, 2, 3],
+            cache: 42,
+        };
+
+        let branch = device.to_branch();
+        let args = branch.as_args().unwrap();
+        assert_eq!(
+            args.get_names_raw(),
+            &["name", "gain", "main", "ports", "ids"]
+        );
+        assert!(args.get_value("main", "Value").unwrap().is_struct());
+        assert!(args.get_value("ports", "Value").unwrap().is_array());
+        assert!(args
+            .get_value("main", "Value")
+            .and_then(|main| main.as_struct())
+            .and_then(|main| main.as_args())
+            .and_then(|main| main.get_value("Open", "Value"))
+            .is_some());
+
+        assert_eq!(
+            Device::from_branch(&branch),
+            Ok(Device { cache: 0, ..device })
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{branch, state::State, Branch, Branches, Node, Outcome, Value};
+
+#[derive(Debug, Clone)]
+pub(crate) enum Change<'a> {
+    Edit {
+        path: Vec<Node>,
+        old: String,
+        new: String,
+    },
+    Toggle {
+        path: Vec<Node>,
+    },
+    Replace {
+        path: Vec<Node>,
+        old: Value<'a>,
+        new: Value<'a>,
+    },
+    Swap {
+        path: Vec<Node>,
+        old: Branch<'a>,
+        new: Branch<'a>,
+    },
+    Insert {
+        path: Vec<Node>,
+        index: usize,
+        element: Branch<'a>,
+    },
+    Remove {
+        path: Vec<Node>,
+        index: usize,
+        element: Branch<'a>,
+    },
+    Move {
+        path: Vec<Node>,
+        from: usize,
+        to: usize,
+    },
+    Rename {
+        path: Vec<Node>,
+        new: String,
+    },
+    Attach {
+        path: Vec<Node>,
+        index: usize,
+        branch: Branch<'a>,
+    },
+    Detach {
+        path: Vec<Node>,
+        index: usize,
+        branch: Branch<'a>,
+    },
+    Group(Vec<Change<'a>>),
+}
+impl<'a> Change<'a> {
+    fn inverse(&self) -> Self {
+        match self.clone() {
+            Self::Edit { path, old, new } => Self::Edit {
+                path,
+                old: new,
+                new: old,
+            },
+            toggle @ Self::Toggle { .. } => toggle,
+            Self::Replace { path, old, new } => Self::Replace {
+                path,
+                old: new,
+                new: old,
+            },
+            Self::Swap { path, old, new } => Self::Swap {
+                path,
+                old: new,
+                new: old,
+            },
+            Self::Move { path, from, to } => Self::Move {
+                path,
+                from: to,
+                to: from,
+            },
+            Self::Rename { mut path, new } => {
+                let old = path.last().map(Node::text).cloned().unwrap_or_default();
+                if let Some(node) = path.last_mut() {
+                    node.change_text(new);
+                }
+                Self::Rename { path, new: old }
+            }
+            Self::Attach {
+                path,
+                index,
+                branch,
+            } => Self::Detach {
+                path,
+                index,
+                branch,
+            },
+            Self::Detach {
+                path,
+                index,
+                branch,
+            } => Self::Attach {
+                path,
+                index,
+                branch,
+            },
+            Self::Group(changes) => Self::Group(changes.iter().rev().map(Self::inverse).collect()),
+            Self::Insert {
+                path,
+                index,
+                element,
+            } => Self::Remove {
+                path,
+                index,
+                element,
+            },
+            Self::Remove {
+                path,
+                index,
+                element,
+            } => Self::Insert {
+                path,
+                index,
+                element,
+            },
+        }
+    }
+
+    pub(crate) fn apply(&self, tabs: &mut Branches<'a>) -> Option<(Vec<Node>, Outcome)> {
+        match self {
+            Self::Edit { path, old, new } => {
+                State::value_at(tabs, path)?.set_text(new);
+                Some((
+                    path.clone(),
+                    Outcome::Committed {
+                        path: path.clone(),
+                        old: old.clone(),
+                        new: new.clone(),
+                    },
+                ))
+            }
+            Self::Toggle { path } => {
+                let value = State::value_at(tabs, path)?.inner_mut().as_bool_mut()?;
+                *value = !*value;
+                Some((
+                    path.clone(),
+                    Outcome::Toggled {
+                        path: path.clone(),
+                        value: *value,
+                    },
+                ))
+            }
+            Self::Replace { path, new, .. } => {
+                let value = State::value_at(tabs, path)?;
+                *value = new.clone();
+                let path = path.clone();
+                Some((
+                    path.clone(),
+                    if value.is_unset() {
+                        Outcome::Cleared { path }
+                    } else {
+                        Outcome::Assigned { path }
+                    },
+                ))
+            }
+            Self::Swap { path, new, .. } => {
+                *State::branch_at(tabs, path)? = new.clone();
+                Some((path.clone(), Outcome::Pasted { path: path.clone() }))
+            }
+            Self::Move { path, from, to } => {
+                let array = State::value_at(tabs, path)?.inner_mut().as_array_mut()?;
+                if !array.move_element(*from, *to) {
+                    return None;
+                }
+                let mut cursor = path.clone();
+                cursor.push(Node::tree(to));
+                Some((
+                    cursor,
+                    Outcome::Moved {
+                        path: path.clone(),
+                        from: *from,
+                        to: *to,
+                    },
+                ))
+            }
+            Self::Rename { path, new } => {
+                let (node, parent) = path.split_last()?;
+                let old = node.text().clone();
+                let renamed = match State::branch_at(tabs, parent)? {
+                    Branch::Map(map) => map.rename(&old, new).is_ok(),
+                    Branch::Tree(tree) => tree.rekey(&old, new),
+                    Branch::Args(args) => args.rename(&old, new),
+                    Branch::Array(_) => false,
+                };
+                if !renamed {
+                    return None;
+                }
+                let mut cursor = path.clone();
+                cursor.last_mut()?.change_text(new.clone());
+                Some((
+                    cursor.clone(),
+                    Outcome::Renamed {
+                        path: cursor,
+                        old,
+                        new: new.clone(),
+                    },
+                ))
+            }
+            Self::Attach {
+                path,
+                index,
+                branch,
+            } => {
+                let (node, parent) = path.split_last()?;
+                let branches = State::branches_at(tabs, parent)?;
+                if branches.contains_key(node.text()) {
+                    return None;
+                }
+                branch::insert(branches, *index, node.text().clone(), branch.clone());
+                let mut cursor = path.clone();
+                if parent.is_empty() {
+                    cursor.push(Node::from(branch));
+                }
+                Some((cursor, Outcome::Created { path: path.clone() }))
+            }
+            Self::Detach { path, index, .. } => {
+                let (node, parent) = path.split_last()?;
+                let branches = State::branches_at(tabs, parent)?;
+                branches.remove(node.text())?;
+                let mut cursor = parent.to_vec();
+                match branches.iter().nth(*index).or_else(|| branches.back()) {
+                    Some((name, branch)) => {
+                        cursor.push(Node::tree(name));
+                        if parent.is_empty() {
+                            cursor.push(Node::from(branch));
+                        }
+                    }
+                    None if !parent.is_empty() => cursor.push(Node::tree("")),
+                    None => (),
+                }
+                Some((cursor, Outcome::Deleted { path: path.clone() }))
+            }
+            Self::Group(changes) => changes
+                .iter()
+                .fold(None, |result, change| change.apply(tabs).or(result)),
+            Self::Insert {
+                path,
+                index,
+                element,
+            } => {
+                let array = State::value_at(tabs, path)?.inner_mut().as_array_mut()?;
+                array.insert(Some(*index), Value::from(element.clone()));
+                let mut cursor = path.clone();
+                cursor.push(Node::tree(index));
+                Some((
+                    cursor,
+                    Outcome::Inserted {
+                        path: path.clone(),
+                        index: *index,
+                    },
+                ))
+            }
+            Self::Remove { path, index, .. } => {
+                let array = State::value_at(tabs, path)?.inner_mut().as_array_mut()?;
+                array.element(*index)?;
+                let mut cursor = path.clone();
+                if let Some(current) = array.remove(&index.to_string()) {
+                    cursor.push(Node::tree(current));
+                }
+                Some((
+                    cursor,
+                    Outcome::Removed {
+                        path: path.clone(),
+                        index: *index,
+                    },
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct History<'a> {
+    undo: Vec<Change<'a>>,
+    redo: Vec<Change<'a>>,
+}
+impl<'a> History<'a> {
+    pub fn record(&mut self, change: Change<'a>) {
+        self.undo.push(change);
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, tabs: &mut Branches<'a>) -> Option<(Vec<Node>, Outcome)> {
+        let change = self.undo.pop()?;
+        let result = change.inverse().apply(tabs);
+        self.redo.push(change);
+        result
+    }
+
+    pub fn redo(&mut self, tabs: &mut Branches<'a>) -> Option<(Vec<Node>, Outcome)> {
+        let change = self.redo.pop()?;
+        let result = change.apply(tabs);
+        self.undo.push(change);
+        result
+    }
+}
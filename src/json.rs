@@ -0,0 +1,346 @@
+use crate::{Args, Branch, NumberType, StringType, Tree, Type, Value, ValueError};
+use serde_json::{Map, Number, Value as JsonValue};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Null,
+    Bool,
+    Integer(i128, i128),
+    Float,
+    String,
+    Text,
+    Optional(Box<Shape>),
+    Array(Option<Box<Shape>>),
+    Object(Vec<(String, Shape)>),
+}
+impl Shape {
+    fn of(json: &JsonValue) -> Result<Self, ValueError> {
+        Ok(match json {
+            JsonValue::Null => Self::Null,
+            JsonValue::Bool(_) => Self::Bool,
+            JsonValue::Number(number) => match integer(number) {
+                Some(number) => Self::Integer(number, number),
+                None => Self::Float,
+            },
+            JsonValue::String(text) if text.contains('\n') => Self::Text,
+            JsonValue::String(_) => Self::String,
+            JsonValue::Array(values) => Self::Array(
+                values
+                    .iter()
+                    .try_fold(None, |shape: Option<Self>, json| {
+                        let element = Self::of(json)?;
+                        shape
+                            .map_or(Ok(element.clone()), |shape| shape.merge(element))
+                            .map(Some)
+                    })?
+                    .map(Box::new),
+            ),
+            JsonValue::Object(object) => Self::Object(
+                object
+                    .iter()
+                    .map(|(name, json)| Ok((name.clone(), Self::of(json)?)))
+                    .collect::<Result<_, ValueError>>()?,
+            ),
+        })
+    }
+
+    fn merge(self, other: Self) -> Result<Self, ValueError> {
+        let mismatch = |this: &Self, other: &Self| ValueError::Mismatch {
+            expected: this.get_type(),
+            found: other.get_type(),
+        };
+        match (self, other) {
+            (Self::Null, Self::Null) => Ok(Self::Null),
+            (Self::Null, Self::Optional(shape)) | (Self::Optional(shape), Self::Null) => {
+                Ok(Self::Optional(shape))
+            }
+            (Self::Null, shape) | (shape, Self::Null) => Ok(Self::Optional(Box::new(shape))),
+            (Self::Optional(l), Self::Optional(r)) => Ok(Self::Optional(Box::new(l.merge(*r)?))),
+            (Self::Optional(l), r) => Ok(Self::Optional(Box::new(l.merge(r)?))),
+            (l, Self::Optional(r)) => Ok(Self::Optional(Box::new(l.merge(*r)?))),
+            (Self::Integer(l_min, l_max), Self::Integer(r_min, r_max)) => {
+                let (min, max) = (l_min.min(r_min), l_max.max(r_max));
+                number_type(min, max).ok_or_else(|| {
+                    mismatch(&Self::Integer(l_min, l_max), &Self::Integer(r_min, r_max))
+                })?;
+                Ok(Self::Integer(min, max))
+            }
+            (Self::Integer(..) | Self::Float, Self::Integer(..) | Self::Float) => Ok(Self::Float),
+            (Self::String, Self::Text) | (Self::Text, Self::String) => Ok(Self::Text),
+            (Self::Array(l), Self::Array(r)) => Ok(Self::Array(match (l, r) {
+                (Some(l), Some(r)) => Some(Box::new(l.merge(*r)?)),
+                (l, r) => l.or(r),
+            })),
+            (Self::Object(l), Self::Object(r))
+                if l.len() == r.len() && l.iter().zip(&r).all(|((l, _), (r, _))| l == r) =>
+            {
+                Ok(Self::Object(
+                    l.into_iter()
+                        .zip(r)
+                        .map(|((name, l), (_, r))| Ok((name, l.merge(r)?)))
+                        .collect::<Result<_, ValueError>>()?,
+                ))
+            }
+            (this, other) if this == other => Ok(this),
+            (this, other) => Err(mismatch(&this, &other)),
+        }
+    }
+
+    fn get_type(&self) -> Type {
+        match self {
+            Self::Null => Type::None,
+            Self::Bool => Type::Bool,
+            Self::Integer(min, max) => {
+                Type::Number(number_type(*min, *max).unwrap_or(NumberType::I64))
+            }
+            Self::Float => Type::Number(NumberType::F64),
+            Self::String => Type::String(StringType::String),
+            Self::Text => Type::String(StringType::Text),
+            Self::Optional(shape) => Type::Option(Box::new(shape.get_type())),
+            Self::Array(element) => Type::Array(Box::new(
+                element
+                    .as_ref()
+                    .map_or(Type::None, |element| element.get_type()),
+            )),
+            Self::Object(_) => Type::Struct,
+        }
+    }
+
+    fn placeholder(&self) -> JsonValue {
+        match self {
+            Self::Null => JsonValue::Null,
+            Self::Bool => false.into(),
+            Self::Integer(..) => 0.into(),
+            Self::Float => 0.0.into(),
+            Self::String | Self::Text => String::new().into(),
+            Self::Optional(shape) => shape.placeholder(),
+            Self::Array(_) => JsonValue::Array(Vec::new()),
+            Self::Object(fields) => fields
+                .iter()
+                .map(|(name, shape)| (name.clone(), shape.placeholder()))
+                .collect::<Map<_, _>>()
+                .into(),
+        }
+    }
+}
+
+fn integer(number: &Number) -> Option<i128> {
+    number
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| number.as_u64().map(i128::from))
+}
+
+fn number_type(min: i128, max: i128) -> Option<NumberType> {
+    let types = if min >= 0 {
+        [
+            (NumberType::U8, u8::MIN.into(), u8::MAX.into()),
+            (NumberType::U16, u16::MIN.into(), u16::MAX.into()),
+            (NumberType::U32, u32::MIN.into(), u32::MAX.into()),
+            (NumberType::U64, u64::MIN.into(), u64::MAX.into()),
+        ]
+    } else {
+        [
+            (NumberType::I8, i8::MIN.into(), i8::MAX.into()),
+            (NumberType::I16, i16::MIN.into(), i16::MAX.into()),
+            (NumberType::I32, i32::MIN.into(), i32::MAX.into()),
+            (NumberType::I64, i64::MIN.into(), i64::MAX.into()),
+        ]
+    };
+    types
+        .into_iter()
+        .find(|(_, lower, upper): &(_, i128, i128)| *lower <= min && max <= *upper)
+        .map(|(ty, _, _)| ty)
+}
+
+fn number<'a>(number: i128, ty: NumberType) -> Value<'a> {
+    match ty {
+        NumberType::U8 => (number as u8).into(),
+        NumberType::I8 => (number as i8).into(),
+        NumberType::U16 => (number as u16).into(),
+        NumberType::I16 => (number as i16).into(),
+        NumberType::U32 => (number as u32).into(),
+        NumberType::I32 => (number as i32).into(),
+        NumberType::U64 => (number as u64).into(),
+        NumberType::I64 => (number as i64).into(),
+        NumberType::F32 => (number as f32).into(),
+        NumberType::F64 => (number as f64).into(),
+        NumberType::Usize => (number as usize).into(),
+        NumberType::Isize => (number as isize).into(),
+    }
+}
+
+fn value<'a>(json: JsonValue, shape: &Shape) -> Value<'a> {
+    match (json, shape) {
+        (JsonValue::Null, Shape::Optional(shape)) => {
+            let mut value = value(shape.placeholder(), shape).into_option();
+            value.clear();
+            value
+        }
+        (json, Shape::Optional(shape)) => value(json, shape).into_option(),
+        (JsonValue::Bool(value), _) => value.into(),
+        (JsonValue::Number(value), Shape::Integer(min, max)) => number(
+            integer(&value).unwrap_or_default(),
+            number_type(*min, *max).unwrap_or(NumberType::I64),
+        ),
+        (JsonValue::Number(value), _) => value.as_f64().unwrap_or_default().into(),
+        (JsonValue::String(value), Shape::Text) => Value::multiline(value),
+        (JsonValue::String(value), _) => value.into(),
+        (JsonValue::Array(values), Shape::Array(element)) => {
+            let element = element.as_deref().unwrap_or(&Shape::Null);
+            let mut array = value(element.placeholder(), element).into_array();
+            if let Some(tree) = array.as_array_mut() {
+                values
+                    .into_iter()
+                    .for_each(|json| tree.insert(None, value(json, element)));
+            }
+            array
+        }
+        (JsonValue::Object(object), Shape::Object(fields)) => branch(object, fields).into(),
+        _ => Value::default(),
+    }
+}
+
+fn branch<'a>(object: Map<String, JsonValue>, fields: &[(String, Shape)]) -> Branch<'a> {
+    if !fields.is_empty()
+        && fields
+            .iter()
+            .all(|(_, shape)| matches!(shape, Shape::Object(_)))
+    {
+        return object
+            .into_iter()
+            .zip(fields)
+            .fold(Tree::default(), |tree, ((name, json), (_, shape))| {
+                match (json, shape) {
+                    (JsonValue::Object(object), Shape::Object(fields)) => {
+                        tree.branch(name, branch(object, fields))
+                    }
+                    _ => tree,
+                }
+            })
+            .into();
+    }
+    let args = Args::default().names(object.keys()).columns(["Value"]);
+    object
+        .into_iter()
+        .zip(fields)
+        .fold(args, |args, ((name, json), (_, shape))| {
+            args.value(name, "Value", value(json, shape))
+        })
+        .into()
+}
+
+impl<'a> TryFrom<JsonValue> for Branch<'a> {
+    type Error = ValueError;
+
+    fn try_from(json: JsonValue) -> Result<Self, Self::Error> {
+        match (Shape::of(&json)?, json) {
+            (Shape::Object(fields), JsonValue::Object(object)) => Ok(branch(object, &fields)),
+            (shape, json) => Ok(Args::default()
+                .names([""])
+                .columns(["Value"])
+                .value("", "Value", value(json, &shape))
+                .into()),
+        }
+    }
+}
+
+impl<'a> TryFrom<JsonValue> for Value<'a> {
+    type Error = ValueError;
+
+    fn try_from(json: JsonValue) -> Result<Self, Self::Error> {
+        let shape = Shape::of(&json)?;
+        Ok(value(json, &shape))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Branch, NumberType, StringType, TreeEdit, Type, Value, ValueError};
+    use serde_json::json;
+
+    #[test]
+    fn it_works() {
+        let document = json!({
+            "Network": {
+                "eth0": { "Address": "192.168.0.1", "Mask": 24, "Up": true },
+                "eth1": { "Address": "10.0.0.1", "Mask": 8, "Up": false }
+            },
+            "Ports": { "List": [80, 443], "Gain": -1.5, "Limits": { "Min": 1 } }
+        });
+        let tree_edit = TreeEdit::new("Test").tab(
+            "Device".to_string(),
+            Branch::try_from(document.clone()).unwrap(),
+        );
+
+        let Some((_, Branch::Tree(tree))) = tree_edit.get_tabs().front() else {
+            panic!("tab must be a tree");
+        };
+        assert!(tree.get_branches()["Network"].is_tree());
+        assert!(tree.get_branches()["Ports"].is_args());
+        let ports = tree.get_branches()["Ports"].as_args().unwrap();
+        assert_eq!(ports.get_columns_raw(), &vec!["Value".to_string()]);
+        assert_eq!(
+            tree.get_branches()["Network"]
+                .as_tree()
+                .and_then(|network| network.get_branches()["eth0"].as_args())
+                .and_then(|eth0| eth0.get_value("Mask", "Value"))
+                .unwrap()
+                .get_type(),
+            &Type::Number(NumberType::U8)
+        );
+        assert_eq!(
+            ports.get_value("List", "Value").unwrap().get_type(),
+            &Type::Array(Box::new(Type::Number(NumberType::U16)))
+        );
+        assert_eq!(
+            serde_json::to_value(&tree_edit).unwrap(),
+            json!({ "Device": document })
+        );
+    }
+
+    #[test]
+    fn mixed_arrays() {
+        let value = Value::try_from(json!([1, -300, 2.5])).unwrap();
+        assert_eq!(
+            value.get_type(),
+            &Type::Array(Box::new(Type::Number(NumberType::F64)))
+        );
+        assert_eq!(value.get(), Ok(vec![1.0, -300.0, 2.5]));
+        assert_eq!(
+            Value::try_from(json!([[1], [-300]])).unwrap().get_type(),
+            &Type::Array(Box::new(Type::Array(Box::new(Type::Number(
+                NumberType::I16
+            )))))
+        );
+        assert_eq!(
+            Value::try_from(json!(["a", "b"])).unwrap().get_type(),
+            &Type::Array(Box::new(Type::String(StringType::String)))
+        );
+        assert_eq!(
+            Value::try_from(json!(["a", "b\nc"])).unwrap().get_type(),
+            &Type::Array(Box::new(Type::String(StringType::Text)))
+        );
+        assert_eq!(
+            Value::try_from(json!([1, 2.5, "x"])).err(),
+            Some(ValueError::Mismatch {
+                expected: Type::Number(NumberType::F64),
+                found: Type::String(StringType::String),
+            })
+        );
+        assert!(Branch::try_from(json!({ "List": [{ "a": 1 }, { "b": 1 }] })).is_err());
+    }
+
+    #[test]
+    fn nulls() {
+        let value = Value::try_from(json!([1, null])).unwrap();
+        assert_eq!(
+            value.get_type(),
+            &Type::Array(Box::new(Type::Option(Box::new(Type::Number(
+                NumberType::U8
+            )))))
+        );
+        assert_eq!(value.get(), Ok(vec![Some(1u8), None]));
+        assert_eq!(serde_json::to_value(&value).unwrap(), json!([1, null]));
+    }
+}
@@ -0,0 +1,242 @@
+use crate::Event;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Ctrl(char),
+    Alt(char),
+    Backspace,
+    Enter,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Tab,
+    BackTab,
+    Delete,
+    Insert,
+    F(u8),
+    Esc,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: HashMap<Key, Event>,
+}
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: [
+                (Key::Tab, Event::NextTab),
+                (Key::BackTab, Event::PreviousTab),
+                (Key::Down, Event::NextItem),
+                (Key::Up, Event::PreviousItem),
+                (Key::Right, Event::NextLevel),
+                (Key::Left, Event::PreviousLevel),
+                (Key::Enter, Event::Enter),
+                (Key::Esc, Event::Cancel),
+                (Key::Backspace, Event::Backspace),
+                (Key::Delete, Event::Delete),
+                (Key::Ctrl('n'), Event::NewLine),
+                (Key::Home, Event::Home),
+                (Key::End, Event::End),
+                (Key::Alt('b'), Event::WordLeft),
+                (Key::Alt('f'), Event::WordRight),
+                (Key::Ctrl('w'), Event::DeleteWord),
+                (Key::Ctrl('c'), Event::Copy),
+                (Key::Alt('c'), Event::CopyRow),
+                (Key::Ctrl('x'), Event::Cut),
+                (Key::Ctrl('k'), Event::CutRow),
+                (Key::Ctrl('v'), Event::Paste),
+                (Key::Insert, Event::InsertAfter),
+                (Key::Alt('i'), Event::InsertBefore),
+                (Key::Ctrl('d'), Event::Duplicate),
+                (Key::Alt('k'), Event::MoveUp),
+                (Key::Alt('j'), Event::MoveDown),
+                (Key::Ctrl('f'), Event::Search),
+                (Key::F(2), Event::Rename),
+                (Key::Ctrl('t'), Event::NewTab),
+                (Key::Ctrl('b'), Event::NewBranch),
+                (Key::Alt('t'), Event::CloneTab),
+                (Key::Alt('d'), Event::CloneBranch),
+                (Key::Alt('w'), Event::RemoveTab),
+                (Key::Alt('x'), Event::RemoveBranch),
+                (Key::Alt('n'), Event::NextMatch),
+                (Key::Alt('p'), Event::PreviousMatch),
+                (Key::Ctrl('z'), Event::Undo),
+                (Key::Ctrl('y'), Event::Redo),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+}
+impl KeyMap {
+    pub fn empty() -> Self {
+        Self {
+            bindings: Default::default(),
+        }
+    }
+
+    pub fn bind(mut self, key: Key, event: Event) -> Self {
+        self.bindings.insert(key, event);
+        self
+    }
+    pub fn unbind(mut self, key: Key) -> Self {
+        self.bindings.remove(&key);
+        self
+    }
+
+    pub fn get_bindings(&self) -> &HashMap<Key, Event> {
+        &self.bindings
+    }
+
+    pub fn event(&self, key: impl Into<Key>) -> Option<Event> {
+        let key = key.into();
+        self.bindings.get(&key).cloned().or(match key {
+            Key::Char(sym) => Some(Event::Char(sym)),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(feature = "crossterm")]
+mod crossterm {
+    use super::{Key, KeyMap};
+    use crate::Event;
+    use crossterm::event::{
+        Event as CrosstermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+    };
+
+    impl TryFrom<KeyEvent> for Key {
+        type Error = KeyEvent;
+
+        fn try_from(event: KeyEvent) -> Result<Self, Self::Error> {
+            if event.kind == KeyEventKind::Release {
+                return Err(event);
+            }
+            Ok(match event.code {
+                KeyCode::Char(sym) if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Key::Ctrl(sym)
+                }
+                KeyCode::Char(sym) if event.modifiers.contains(KeyModifiers::ALT) => Key::Alt(sym),
+                KeyCode::Char(sym) => Key::Char(sym),
+                KeyCode::Backspace => Key::Backspace,
+                KeyCode::Enter => Key::Enter,
+                KeyCode::Left => Key::Left,
+                KeyCode::Right => Key::Right,
+                KeyCode::Up => Key::Up,
+                KeyCode::Down => Key::Down,
+                KeyCode::Home => Key::Home,
+                KeyCode::End => Key::End,
+                KeyCode::PageUp => Key::PageUp,
+                KeyCode::PageDown => Key::PageDown,
+                KeyCode::Tab => Key::Tab,
+                KeyCode::BackTab => Key::BackTab,
+                KeyCode::Delete => Key::Delete,
+                KeyCode::Insert => Key::Insert,
+                KeyCode::F(number) => Key::F(number),
+                KeyCode::Esc => Key::Esc,
+                _ => return Err(event),
+            })
+        }
+    }
+
+    impl KeyMap {
+        pub fn crossterm(&self, event: KeyEvent) -> Option<Event> {
+            Key::try_from(event).ok().and_then(|key| self.event(key))
+        }
+
+        pub fn crossterm_event(&self, event: CrosstermEvent) -> Option<Event> {
+            match event {
+                CrosstermEvent::Key(event) => self.crossterm(event),
+                CrosstermEvent::Paste(text) => Some(Event::InsertStr(text)),
+                _ => None,
+            }
+        }
+    }
+
+    impl TryFrom<KeyEvent> for Event {
+        type Error = KeyEvent;
+
+        fn try_from(event: KeyEvent) -> Result<Self, Self::Error> {
+            KeyMap::default().crossterm(event).ok_or(event)
+        }
+    }
+}
+
+#[cfg(feature = "termion")]
+mod termion {
+    use super::{Key, KeyMap};
+    use crate::Event;
+    use termion::event::Key as TermionKey;
+
+    impl TryFrom<TermionKey> for Key {
+        type Error = TermionKey;
+
+        fn try_from(key: TermionKey) -> Result<Self, Self::Error> {
+            Ok(match key {
+                TermionKey::Char('\n') => Key::Enter,
+                TermionKey::Char('\t') => Key::Tab,
+                TermionKey::Char(sym) => Key::Char(sym),
+                TermionKey::Ctrl(sym) => Key::Ctrl(sym),
+                TermionKey::Alt(sym) => Key::Alt(sym),
+                TermionKey::Backspace => Key::Backspace,
+                TermionKey::Left => Key::Left,
+                TermionKey::Right => Key::Right,
+                TermionKey::Up => Key::Up,
+                TermionKey::Down => Key::Down,
+                TermionKey::Home => Key::Home,
+                TermionKey::End => Key::End,
+                TermionKey::PageUp => Key::PageUp,
+                TermionKey::PageDown => Key::PageDown,
+                TermionKey::BackTab => Key::BackTab,
+                TermionKey::Delete => Key::Delete,
+                TermionKey::Insert => Key::Insert,
+                TermionKey::F(number) => Key::F(number),
+                TermionKey::Esc => Key::Esc,
+                key => return Err(key),
+            })
+        }
+    }
+
+    impl KeyMap {
+        pub fn termion(&self, key: TermionKey) -> Option<Event> {
+            Key::try_from(key).ok().and_then(|key| self.event(key))
+        }
+    }
+
+    impl TryFrom<TermionKey> for Event {
+        type Error = TermionKey;
+
+        fn try_from(key: TermionKey) -> Result<Self, Self::Error> {
+            KeyMap::default().termion(key).ok_or(key)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Key, KeyMap};
+    use crate::Event;
+
+    #[test]
+    fn it_works() {
+        let keymap = KeyMap::default()
+            .bind(Key::Char('l'), Event::NextLevel)
+            .bind(Key::Ctrl('d'), Event::Delete)
+            .unbind(Key::Delete);
+
+        assert_eq!(keymap.event(Key::Char('l')), Some(Event::NextLevel));
+        assert_eq!(keymap.event(Key::Char('k')), Some(Event::Char('k')));
+        assert_eq!(keymap.event(Key::Ctrl('d')), Some(Event::Delete));
+        assert_eq!(keymap.event(Key::Delete), None);
+        assert_eq!(keymap.event(Key::Esc), Some(Event::Cancel));
+        assert_eq!(keymap.event(Key::Home), Some(Event::Home));
+    }
+}
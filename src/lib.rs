@@ -1,22 +1,39 @@
+extern crate self as tui_va_tree_edit;
+
 pub use argument::{
-    value::{NumberType, StringType, Type, Value},
+    value::{Access, FromValue, GetType, NumberType, Rule, StringType, Type, Value, ValueError},
     Args,
 };
 pub use array::Array;
 pub use branch::{Branch, Branches};
-pub use state::Node;
+pub use clipboard::{Clip, SystemClipboard};
+pub use editable::TreeEditable;
+pub use keymap::{Key, KeyMap};
+pub use map::Map;
+pub use path::{IntoPath, PathError};
+pub use state::{Node, State as TreeEditState};
 pub use tree::Tree;
 
-use tui::style::Style;
-
 mod argument;
 mod array;
 mod branch;
+mod clipboard;
+mod dirty;
+mod editable;
+mod history;
+#[cfg(feature = "json")]
+mod json;
+mod keymap;
+mod map;
+mod path;
+mod search;
+#[cfg(feature = "serde")]
+mod ser;
 pub mod state;
 mod tree;
 mod widget;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Event {
     NextTab,
     PreviousTab,
@@ -29,66 +46,258 @@ pub enum Event {
     Backspace,
     Delete,
     Char(char),
+    NewLine,
+    Home,
+    End,
+    WordLeft,
+    WordRight,
+    DeleteWord,
+    InsertStr(String),
+    Copy,
+    CopyRow,
+    Cut,
+    CutRow,
+    Paste,
+    InsertBefore,
+    InsertAfter,
+    Duplicate,
+    MoveUp,
+    MoveDown,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Rename,
+    NewTab,
+    NewBranch,
+    CloneTab,
+    CloneBranch,
+    RemoveTab,
+    RemoveBranch,
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    None,
+    Committed {
+        path: Vec<Node>,
+        old: String,
+        new: String,
+    },
+    Cancelled {
+        path: Vec<Node>,
+    },
+    Inserted {
+        path: Vec<Node>,
+        index: usize,
+    },
+    Removed {
+        path: Vec<Node>,
+        index: usize,
+    },
+    Moved {
+        path: Vec<Node>,
+        from: usize,
+        to: usize,
+    },
+    Renamed {
+        path: Vec<Node>,
+        old: String,
+        new: String,
+    },
+    Toggled {
+        path: Vec<Node>,
+        value: bool,
+    },
+    Assigned {
+        path: Vec<Node>,
+    },
+    Cleared {
+        path: Vec<Node>,
+    },
+    Copied {
+        path: Vec<Node>,
+    },
+    Pasted {
+        path: Vec<Node>,
+    },
+    Rejected {
+        path: Vec<Node>,
+        reason: String,
+    },
+    Found {
+        path: Vec<Node>,
+        index: usize,
+        count: usize,
+    },
+    Creating {
+        path: Vec<Node>,
+        template: Option<String>,
+    },
+    Created {
+        path: Vec<Node>,
+    },
+    Confirm {
+        path: Vec<Node>,
+    },
+    Deleted {
+        path: Vec<Node>,
+    },
+    TabSwitched(String),
 }
 
+pub type Template<'a> = std::sync::Arc<dyn Fn() -> Branch<'a> + Send + Sync + 'a>;
+
 #[derive(Default, Clone)]
 pub struct TreeEdit<'a> {
     title: String,
     tabs: Branches<'a>,
-    state: state::State,
+    baseline: Branches<'a>,
+    templates: Vec<(String, Template<'a>)>,
 }
 impl TreeEdit<'_> {
     pub fn new<T: ToString>(title: T) -> Self {
         Self {
             title: title.to_string(),
             tabs: Default::default(),
-            state: Default::default(),
+            baseline: Default::default(),
+            templates: Default::default(),
         }
     }
 }
 
 impl<'a> TreeEdit<'a> {
     pub fn tab(mut self, tab_name: String, tab: impl Into<Branch<'a>>) -> Self {
-        self.tabs.insert(tab_name, tab.into());
-        if self.get_index_tab().is_none() {
-            self.transition(Event::PreviousTab)
-        }
+        let tab = tab.into();
+        self.baseline.insert(tab_name.clone(), tab.clone());
+        self.tabs.insert(tab_name, tab);
+        self
+    }
+    pub fn template(
+        mut self,
+        name: impl ToString,
+        factory: impl Fn() -> Branch<'a> + Send + Sync + 'a,
+    ) -> Self {
+        self.templates
+            .push((name.to_string(), std::sync::Arc::new(factory)));
         self
     }
+    pub fn get_templates(&self) -> Vec<&String> {
+        self.templates.iter().map(|(name, _)| name).collect()
+    }
 
+    pub fn get_title(&self) -> &String {
+        &self.title
+    }
     pub fn get_tabs(&self) -> &Branches<'a> {
         &self.tabs
     }
-
-    pub fn widget<'b>(&'b self) -> widget::Drawer<'a, 'b> {
-        widget::Drawer::new(self)
+    pub fn get_tabs_mut(&mut self) -> &mut Branches<'a> {
+        &mut self.tabs
     }
 
-    fn get_index_tab(&self) -> Option<usize> {
-        self.state.index_tab(&self.tabs)
+    pub fn get_at(&self, path: impl IntoPath) -> Result<&Value<'a>, PathError> {
+        match path::walk(&self.tabs, path)? {
+            (_, path::Target::Cell(Some(value))) => Ok(value),
+            (nodes, _) => Err(PathError::Cell(
+                nodes.last().map(Node::text).cloned().unwrap_or_default(),
+            )),
+        }
     }
-    pub fn get_current_tab(&self) -> Option<(&String, &Branch<'a>)> {
-        self.tabs.iter().nth(self.get_index_tab().unwrap_or(0))
+    pub fn set_at(
+        &mut self,
+        path: impl IntoPath,
+        value: impl Into<Value<'a>>,
+    ) -> Result<Value<'a>, PathError> {
+        let nodes = path::resolve(&self.tabs, path)?;
+        let name = nodes.last().map(Node::text).cloned().unwrap_or_default();
+        let current =
+            state::State::value_at(&mut self.tabs, &nodes).ok_or(PathError::Cell(name))?;
+        let value = value.into();
+        let old = current.clone();
+        if !current.assign(&value) {
+            return Err(ValueError::Mismatch {
+                expected: old.get_type().clone(),
+                found: value.get_type().clone(),
+            }
+            .into());
+        }
+        if current.validate().is_err() {
+            let error = ValueError::Invalid {
+                ty: old.get_type().clone(),
+                text: value.to_string(),
+            };
+            *current = old;
+            return Err(error.into());
+        }
+        Ok(old)
     }
-
-    pub fn transition(&mut self, event: Event) {
-        self.state.transition(event, &mut self.tabs)
+    pub fn remove_at(&mut self, path: impl IntoPath) -> Result<Branch<'a>, PathError> {
+        let nodes = path::resolve(&self.tabs, path)?;
+        let Some((node, parent)) = nodes.split_last() else {
+            return Err(PathError::Empty);
+        };
+        let name = node.text();
+        if node.is_args() {
+            return Err(PathError::Fixed(name.clone()));
+        }
+        if parent.is_empty() {
+            return self
+                .tabs
+                .remove(name)
+                .ok_or_else(|| PathError::Tab(name.clone()));
+        }
+        match state::State::branch_at(&mut self.tabs, parent) {
+            Some(Branch::Tree(tree)) => tree.get_branches_mut().remove(name),
+            Some(Branch::Map(map)) => map.tree.branches.remove(name),
+            Some(Branch::Array(array)) => {
+                if array.check_remove().is_err() {
+                    return Err(PathError::Fixed(name.clone()));
+                }
+                let element = array.get_branches().get(name).cloned();
+                array.remove(name);
+                element
+            }
+            _ => None,
+        }
+        .ok_or_else(|| PathError::Branch(name.clone()))
     }
 
-    pub fn position(&self) -> &Vec<Node> {
-        &self.state.position
+    pub fn dirty_paths(&self) -> Vec<Vec<Node>> {
+        dirty::paths(&self.tabs, &self.baseline)
     }
-
-    pub fn style(mut self, style: Style) -> Self {
-        self.state.style = style;
-        self
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty_paths().is_empty()
     }
-    pub fn highlight_style(mut self, style: Style) -> Self {
-        self.state.highlight_style = style;
-        self
+    pub fn reset_at(&mut self, path: impl IntoPath) -> Result<(), PathError> {
+        let nodes = path::resolve(&self.tabs, path)?;
+        let name = nodes.last().map(Node::text).cloned().unwrap_or_default();
+        if nodes.last().map_or(false, Node::is_args) {
+            let original = state::State::value_at(&mut self.baseline, &nodes)
+                .cloned()
+                .ok_or(PathError::Cell(name.clone()))?;
+            let value =
+                state::State::value_at(&mut self.tabs, &nodes).ok_or(PathError::Cell(name))?;
+            *value = original;
+        } else {
+            let original = state::State::branch_at(&mut self.baseline, &nodes)
+                .cloned()
+                .ok_or(PathError::Branch(name.clone()))?;
+            let branch =
+                state::State::branch_at(&mut self.tabs, &nodes).ok_or(PathError::Branch(name))?;
+            *branch = original;
+        }
+        Ok(())
+    }
+    pub fn reset_all(&mut self) {
+        self.tabs = self.baseline.clone();
+    }
+    pub fn accept(&mut self) {
+        self.baseline = self.tabs.clone();
     }
 
-    pub fn in_input_mode(&self) -> bool {
-        self.state.input.is_some()
+    pub fn widget<'b>(&'b self) -> widget::Drawer<'a, 'b> {
+        widget::Drawer::new(self)
     }
 }
@@ -0,0 +1,97 @@
+use crate::{array::Array, Branch, Tree, Value};
+use std::ops::Deref;
+
+#[derive(Debug, Clone)]
+pub struct Map<'a> {
+    pub(crate) def: Box<Value<'a>>,
+    pub tree: Tree<'a>,
+}
+impl<'a> Map<'a> {
+    pub(crate) fn new(def: Box<Value<'a>>) -> Self {
+        Self {
+            def,
+            tree: Default::default(),
+        }
+    }
+
+    pub fn is_wrapped(&self) -> bool {
+        self.def.is_struct()
+    }
+
+    pub(crate) fn prototype(&self) -> Branch<'a> {
+        Array::element_of((*self.def).clone())
+    }
+
+    pub(crate) fn new_key(&self) -> String {
+        (0..)
+            .map(|index| match index {
+                0 => "key".to_string(),
+                index => format!("key{index}"),
+            })
+            .find(|key| !self.branches.contains_key(key))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn check_key(&self, old: &str, key: &str) -> Result<(), String> {
+        if key.is_empty() {
+            Err("Empty key".into())
+        } else if key != old && self.branches.contains_key(key) {
+            Err(format!("Key \"{key}\" already exists"))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let prototype = self.prototype();
+        self.branches
+            .iter()
+            .find(|(_, entry)| !entry.same_shape(&prototype))
+            .map_or(Ok(()), |(key, _)| {
+                Err(format!("Entry \"{key}\" has a different layout"))
+            })
+    }
+
+    pub(crate) fn insert(&mut self, key: impl ToString, value: impl Into<Value<'a>>) -> bool {
+        let key = key.to_string();
+        if self.branches.contains_key(&key) {
+            return false;
+        }
+        self.tree
+            .branches
+            .insert(key, Array::element_of(value.into()));
+        true
+    }
+
+    pub(crate) fn insert_default(&mut self) -> String {
+        let key = self.new_key();
+        self.insert(&key, (*self.def).clone());
+        key
+    }
+
+    pub(crate) fn remove(&mut self, key: &String) -> Option<String> {
+        let keys = self.branches.keys().cloned().collect::<Vec<_>>();
+        let index = keys.iter().position(|k| k == key)?;
+        self.tree.branches.remove(key);
+        keys.get(index + 1)
+            .or_else(|| index.checked_sub(1).and_then(|index| keys.get(index)))
+            .cloned()
+    }
+
+    pub(crate) fn rename(&mut self, old: &str, new: &str) -> Result<(), String> {
+        self.check_key(old, new)?;
+        if self.tree.rekey(old, new) {
+            Ok(())
+        } else {
+            Err(format!("No key \"{old}\""))
+        }
+    }
+}
+
+impl<'a> Deref for Map<'a> {
+    type Target = Tree<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tree
+    }
+}
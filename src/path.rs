@@ -0,0 +1,232 @@
+use crate::{array::Array, map::Map, state::Node, Branch, Branches, Value, ValueError};
+use std::{
+    fmt::Display,
+    sync::{atomic::AtomicUsize, Arc},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    Empty,
+    Tab(String),
+    Branch(String),
+    Row(String),
+    Column(String),
+    Leaf(String),
+    Cell(String),
+    Fixed(String),
+    Value(ValueError),
+}
+impl Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathError::Empty => write!(f, "Empty path"),
+            PathError::Tab(name) => write!(f, "No tab \"{name}\""),
+            PathError::Branch(name) => write!(f, "No branch \"{name}\""),
+            PathError::Row(name) => write!(f, "No row \"{name}\""),
+            PathError::Column(name) => write!(f, "No column \"{name}\""),
+            PathError::Leaf(name) => write!(f, "\"{name}\" has no nested branch"),
+            PathError::Cell(name) => write!(f, "\"{name}\" is not a value cell"),
+            PathError::Fixed(name) => write!(f, "\"{name}\" cannot be removed"),
+            PathError::Value(error) => write!(f, "{error}"),
+        }
+    }
+}
+impl std::error::Error for PathError {}
+impl From<ValueError> for PathError {
+    fn from(error: ValueError) -> Self {
+        Self::Value(error)
+    }
+}
+
+pub trait IntoPath {
+    fn into_segments(self) -> Vec<String>;
+}
+impl IntoPath for &str {
+    fn into_segments(self) -> Vec<String> {
+        let mut depth = 0usize;
+        self.split(|sym| {
+            match sym {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+            sym == '/' && depth == 0
+        })
+        .filter(|segment| !segment.is_empty())
+        .map(ToString::to_string)
+        .collect()
+    }
+}
+impl IntoPath for &String {
+    fn into_segments(self) -> Vec<String> {
+        self.as_str().into_segments()
+    }
+}
+impl<S: ToString> IntoPath for &[S] {
+    fn into_segments(self) -> Vec<String> {
+        self.iter().map(ToString::to_string).collect()
+    }
+}
+impl<S: ToString, const N: usize> IntoPath for [S; N] {
+    fn into_segments(self) -> Vec<String> {
+        self.iter().map(ToString::to_string).collect()
+    }
+}
+impl<S: ToString> IntoPath for Vec<S> {
+    fn into_segments(self) -> Vec<String> {
+        self.iter().map(ToString::to_string).collect()
+    }
+}
+
+fn cell(segment: &str) -> (&str, Option<&str>) {
+    segment
+        .strip_prefix('(')
+        .and_then(|cell| cell.strip_suffix(')'))
+        .and_then(|cell| cell.rsplit_once(','))
+        .map_or((segment, None), |(row, column)| {
+            (row.trim(), Some(column.trim()))
+        })
+}
+
+pub(crate) enum Target<'b, 'a> {
+    Branch(&'b Branch<'a>),
+    Cell(Option<&'b Value<'a>>),
+}
+
+pub(crate) fn resolve(tabs: &Branches, path: impl IntoPath) -> Result<Vec<Node>, PathError> {
+    walk(tabs, path).map(|(nodes, _)| nodes)
+}
+
+pub(crate) fn walk<'b, 'a>(
+    tabs: &'b Branches<'a>,
+    path: impl IntoPath,
+) -> Result<(Vec<Node>, Target<'b, 'a>), PathError> {
+    let segments = path.into_segments();
+    let (tab, segments) = segments.split_first().ok_or(PathError::Empty)?;
+    let mut target = Target::Branch(tabs.get(tab).ok_or_else(|| PathError::Tab(tab.clone()))?);
+    let mut nodes = vec![Node::tree(tab)];
+
+    for segment in segments {
+        let branch = match target {
+            Target::Branch(branch) => branch,
+            Target::Cell(value) => value
+                .and_then(|value| value.inner().as_struct())
+                .ok_or_else(|| PathError::Leaf(nodes.last().map_or(tab, Node::text).clone()))?,
+        };
+        let node = match branch {
+            Branch::Args(args) => {
+                let (name, column) = cell(segment);
+                let row = args
+                    .get_names_raw()
+                    .iter()
+                    .position(|row| row == name)
+                    .ok_or_else(|| PathError::Row(name.to_string()))?;
+                let column = column.map_or(Ok(0), |column| {
+                    args.get_columns_raw()
+                        .iter()
+                        .position(|title| title == column)
+                        .ok_or_else(|| PathError::Column(column.to_string()))
+                })?;
+                target = Target::Cell(args.get_value_by_indexes(row, column));
+                Node::Args {
+                    name: name.to_string(),
+                    column,
+                    offset: Arc::new(AtomicUsize::new(row)),
+                }
+            }
+            Branch::Tree(tree)
+            | Branch::Array(Array { tree, .. })
+            | Branch::Map(Map { tree, .. }) => {
+                let (index, (name, next)) = tree
+                    .get_branches()
+                    .iter()
+                    .enumerate()
+                    .find(|(_, (name, _))| *name == segment)
+                    .ok_or_else(|| PathError::Branch(segment.clone()))?;
+                target = Target::Branch(next);
+                Node::Tree {
+                    name: name.clone(),
+                    offset: Arc::new(AtomicUsize::new(index)),
+                }
+            }
+        };
+        nodes.push(node);
+    }
+    Ok((nodes, target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntoPath;
+    use crate::{Args, PathError, Tree, TreeEdit, ValueError};
+
+    #[test]
+    fn it_works() {
+        assert_eq!(
+            "Network/eth0/(Address/Mask, Value)".into_segments(),
+            vec!["Network", "eth0", "(Address/Mask, Value)"]
+        );
+
+        let mut tree_edit = TreeEdit::new("Test").tab(
+            "Network".to_string(),
+            Tree::default().branch(
+                "eth0",
+                Args::default()
+                    .names(["Port", "Gateway", "Routes"])
+                    .columns(["Value"])
+                    .value("Port", "Value", 80u16)
+                    .value("Gateway", "Value", Some("10.0.0.1".to_string()))
+                    .value("Routes", "Value", vec![1u8, 2, 3]),
+            ),
+        );
+
+        assert_eq!(
+            tree_edit.get_at("Network/eth0/Port").unwrap().get(),
+            Ok(80u16)
+        );
+        assert_eq!(
+            tree_edit
+                .set_at("Network/eth0/(Port, Value)", 8080u16)
+                .map(|old| old.get()),
+            Ok(Ok(80u16))
+        );
+        assert_eq!(
+            tree_edit.get_at("Network/eth0/Port").unwrap().get(),
+            Ok(8080u16)
+        );
+        assert!(matches!(
+            tree_edit.set_at("Network/eth0/Port", "8080"),
+            Err(PathError::Value(ValueError::Mismatch { .. }))
+        ));
+        tree_edit
+            .set_at("Network/eth0/Gateway", None::<String>)
+            .unwrap();
+        assert!(tree_edit.get_at("Network/eth0/Gateway").unwrap().is_unset());
+        tree_edit
+            .set_at("Network/eth0/Gateway", "10.0.0.254")
+            .unwrap();
+        assert_eq!(
+            tree_edit.get_at("Network/eth0/Gateway").unwrap().get(),
+            Ok(Some("10.0.0.254".to_string()))
+        );
+        assert_eq!(
+            tree_edit.get_at("Network/eth0").err(),
+            Some(PathError::Cell("eth0".into()))
+        );
+
+        assert!(tree_edit.remove_at("Network/eth0/Routes/1").is_ok());
+        assert_eq!(
+            tree_edit.get_at("Network/eth0/Routes").unwrap().get(),
+            Ok(vec![1u8, 3])
+        );
+        assert_eq!(
+            tree_edit.remove_at("Network/eth0/Port").err(),
+            Some(PathError::Fixed("Port".into()))
+        );
+        assert!(tree_edit.remove_at("Network/eth0").is_ok());
+        assert_eq!(
+            tree_edit.get_at("Network/eth0/Port").err(),
+            Some(PathError::Branch("eth0".into()))
+        );
+    }
+}
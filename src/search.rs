@@ -0,0 +1,57 @@
+use crate::{array::Array, map::Map, state::Node, Branch, Branches, Value};
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Search {
+    pub(crate) query: String,
+    pub(crate) typing: bool,
+    pub(crate) origin: Vec<Node>,
+}
+
+pub(crate) fn is_match(query: &str, text: &str) -> bool {
+    !query.is_empty() && text.to_lowercase().contains(&query.to_lowercase())
+}
+
+pub(crate) fn candidates(tabs: &Branches, query: &str) -> Vec<(Vec<Node>, bool)> {
+    let mut candidates = Vec::new();
+    for (name, branch) in tabs.iter() {
+        let first = candidates.len();
+        collect(branch, vec![Node::tree(name)], query, &mut candidates);
+        if let Some((_, matched)) = candidates.get_mut(first) {
+            *matched |= is_match(query, name);
+        }
+    }
+    candidates
+}
+
+fn collect(branch: &Branch, path: Vec<Node>, query: &str, out: &mut Vec<(Vec<Node>, bool)>) {
+    match branch {
+        Branch::Args(args) => {
+            for (row, name) in args.get_names_raw().iter().enumerate() {
+                for (column, title) in args.get_columns_raw().iter().enumerate() {
+                    let mut path = path.clone();
+                    path.push(Node::args(name, column));
+                    let value = args.get_value_by_indexes(row, column).map(Value::inner);
+                    let matched = column == 0 && is_match(query, name)
+                        || row == 0 && is_match(query, title)
+                        || value
+                            .filter(|value| value.as_struct().is_none() && !value.is_unset())
+                            .map_or(false, |value| {
+                                is_match(query, &value.text().unwrap_or_else(|| value.to_string()))
+                            });
+                    out.push((path.clone(), matched));
+                    if let Some(branch) = value.and_then(Value::as_struct) {
+                        collect(branch, path, query, out);
+                    }
+                }
+            }
+        }
+        Branch::Tree(tree) | Branch::Array(Array { tree, .. }) | Branch::Map(Map { tree, .. }) => {
+            for (name, branch) in tree.get_branches().iter() {
+                let mut path = path.clone();
+                path.push(Node::tree(name));
+                out.push((path.clone(), is_match(query, name)));
+                collect(branch, path, query, out);
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    argument::value::{NumberType, StringType, Type, Value},
+    Args, Array, Branch, Map, Tree, TreeEdit,
+};
+use serde::ser::{Error, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+impl Serialize for TreeEdit<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.tabs.len()))?;
+        for (name, tab) in self.tabs.iter() {
+            map.serialize_entry(name, tab)?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for Branch<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Branch::Args(args) => args.serialize(serializer),
+            Branch::Tree(tree) => tree.serialize(serializer),
+            Branch::Array(array) => array.serialize(serializer),
+            Branch::Map(map) => map.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for Tree<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.branches.len()))?;
+        for (name, branch) in self.branches.iter() {
+            map.serialize_entry(name, branch)?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for Array<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.branches.len()))?;
+        for (_, element) in self.branches.iter() {
+            match element {
+                Branch::Args(args) if !self.is_wrapped() => {
+                    seq.serialize_element(&args.get_value("", "Value"))?
+                }
+                element => seq.serialize_element(element)?,
+            }
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for Map<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.branches.len()))?;
+        for (key, entry) in self.branches.iter() {
+            match entry {
+                Branch::Args(args) if !self.is_wrapped() => {
+                    map.serialize_entry(key, &args.get_value("", "Value"))?
+                }
+                entry => map.serialize_entry(key, entry)?,
+            }
+        }
+        map.end()
+    }
+}
+
+struct Row<'r, 'a>(&'r Args<'a>, usize);
+impl Serialize for Row<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Row(args, row) = self;
+        let mut map = serializer.serialize_map(None)?;
+        for (index, column) in args.get_columns_raw().iter().enumerate() {
+            if let Some(value) = args.get_value_by_indexes(*row, index) {
+                map.serialize_entry(column, value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl Serialize for Args<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let names = self.get_names_raw();
+        let mut map = serializer.serialize_map(Some(names.len()))?;
+        for (index, name) in names.iter().enumerate() {
+            match self.get_columns_raw().as_slice() {
+                [column] if column == "Value" => {
+                    map.serialize_entry(name, &self.get_value_by_indexes(index, 0))?
+                }
+                _ => map.serialize_entry(name, &Row(self, index))?,
+            }
+        }
+        map.end()
+    }
+}
+
+impl Serialize for Value<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        macro_rules! parsed {
+            ($ty:ty) => {
+                self.parse::<$ty>()
+                    .ok_or_else(|| S::Error::custom(format!("invalid {} value", self.get_type())))?
+                    .serialize(serializer)
+            };
+        }
+
+        match self.get_type() {
+            Type::None => serializer.serialize_unit(),
+            Type::Bool => self.as_bool().unwrap_or(&false).serialize(serializer),
+            Type::Number(ty) => match ty {
+                NumberType::U8 => parsed!(u8),
+                NumberType::I8 => parsed!(i8),
+                NumberType::U16 => parsed!(u16),
+                NumberType::I16 => parsed!(i16),
+                NumberType::U32 => parsed!(u32),
+                NumberType::I32 => parsed!(i32),
+                NumberType::U64 => parsed!(u64),
+                NumberType::I64 => parsed!(i64),
+                NumberType::F32 => parsed!(f32),
+                NumberType::F64 => parsed!(f64),
+                NumberType::Usize => parsed!(usize),
+                NumberType::Isize => parsed!(isize),
+            },
+            Type::String(StringType::Char) => parsed!(char),
+            Type::String(StringType::String | StringType::Text) => self
+                .get::<String>()
+                .unwrap_or_default()
+                .serialize(serializer),
+            Type::Enum(_) => self.as_choice().serialize(serializer),
+            Type::Option(_) if self.is_unset() => serializer.serialize_none(),
+            Type::Option(_) => serializer.serialize_some(self.inner()),
+            Type::Array(_) | Type::Map(_) | Type::Struct => match self.as_struct() {
+                Some(branch) => branch.serialize(serializer),
+                None => serializer.serialize_unit(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Args, Tree, TreeEdit};
+
+    #[test]
+    fn it_works() {
+        let tree_edit = TreeEdit::new("Test").tab(
+            "Device".to_string(),
+            Tree::default()
+                .branch(
+                    "Network",
+                    Args::default()
+                        .names(["Address", "Port"])
+                        .columns(["Value"])
+                        .value("Address", "Value", "127.0.0.1")
+                        .value("Port", "Value", 8080u16),
+                )
+                .branch(
+                    "Flags",
+                    Args::default()
+                        .names(["Enabled", "Ids"])
+                        .columns(["Value"])
+                        .value("Enabled", "Value", true)
+                        .value("Ids", "Value", vec![1u8, 2, 3]),
+                ),
+        );
+
+        assert_eq!(
+            serde_json::to_string(&tree_edit).unwrap(),
+            r#"{"Device":{"Network":{"Address":"127.0.0.1","Port":8080},"Flags":{"Enabled":true,"Ids":[1,2,3]}}}"#
+        );
+    }
+}
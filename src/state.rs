@@ -3,7 +3,16 @@ use std::{
     sync::{atomic::AtomicUsize, Arc},
 };
 
-use crate::{array::Array, Branch, Value};
+use crate::{
+    array::Array,
+    branch,
+    history::{Change, History},
+    map::Map,
+    path::{self, IntoPath, PathError},
+    search,
+    widget::Context,
+    Access, Branch, Clip, Outcome, SystemClipboard, TreeEdit, Value,
+};
 use tui::style::Style;
 
 type BranchItem<'a, 'b> = (&'b String, &'b Branch<'a>);
@@ -11,7 +20,10 @@ type BranchItemMut<'a, 'b> = (&'b String, &'b mut Branch<'a>);
 
 #[derive(Debug, Clone)]
 pub enum Node {
-    Tree(String),
+    Tree {
+        name: String,
+        offset: Arc<AtomicUsize>,
+    },
     Args {
         name: String,
         column: usize,
@@ -19,15 +31,29 @@ pub enum Node {
     },
 }
 impl Node {
+    pub fn tree(name: impl ToString) -> Self {
+        Self::Tree {
+            name: name.to_string(),
+            offset: Default::default(),
+        }
+    }
+    pub fn args(name: impl ToString, column: usize) -> Self {
+        Self::Args {
+            name: name.to_string(),
+            column,
+            offset: Default::default(),
+        }
+    }
+
     pub fn is_tree(&self) -> bool {
-        matches!(self, Self::Tree(_))
+        matches!(self, Self::Tree { .. })
     }
     pub fn is_args(&self) -> bool {
         matches!(self, Self::Args { .. })
     }
 
     pub fn as_tree(&self) -> Option<&String> {
-        if let Self::Tree(name) = self {
+        if let Self::Tree { name, .. } = self {
             Some(name)
         } else {
             None
@@ -53,14 +79,16 @@ impl Node {
     }
     pub fn text_mut(&mut self) -> &mut String {
         match self {
-            Node::Tree(t) | Node::Args { name: t, .. } => t,
+            Node::Tree { name, .. } | Node::Args { name, .. } => name,
         }
     }
-    fn change_text(&mut self, text: String) {
-        *match self {
-            Node::Tree(tree) => tree,
-            Node::Args { name: args, .. } => args,
-        } = text
+    pub fn offset(&self) -> Arc<AtomicUsize> {
+        match self {
+            Node::Tree { offset, .. } | Node::Args { offset, .. } => offset.clone(),
+        }
+    }
+    pub(crate) fn change_text(&mut self, text: String) {
+        *self.text_mut() = text
     }
     fn inc_index(&mut self, max: usize) {
         if let Self::Args { column, .. } = self {
@@ -79,48 +107,244 @@ impl Node {
         false
     }
 }
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Tree { name: l_name, .. }, Self::Tree { name: r_name, .. }) => l_name == r_name,
+            (
+                Self::Args {
+                    name: l_name,
+                    column: l_column,
+                    ..
+                },
+                Self::Args {
+                    name: r_name,
+                    column: r_column,
+                    ..
+                },
+            ) => l_name == r_name && l_column == r_column,
+            _ => false,
+        }
+    }
+}
 impl From<&Branch<'_>> for Node {
     fn from(branch: &Branch) -> Self {
+        let name = branch.get_list().first().cloned().unwrap_or_default();
         match branch {
-            Branch::Args(_) => Self::Args {
-                name: branch
-                    .get_list()
-                    .first()
-                    .unwrap_or(&String::default())
-                    .clone(),
-                column: 0,
-                offset: Arc::new(AtomicUsize::new(0)),
-            },
-            Branch::Tree(_) | Branch::Array(_) => Self::Tree(
-                branch
-                    .get_list()
-                    .first()
-                    .unwrap_or(&String::default())
-                    .clone(),
-            ),
+            Branch::Args(_) => Self::args(name, 0),
+            Branch::Tree(_) | Branch::Array(_) | Branch::Map(_) => Self::tree(name),
         }
     }
 }
+#[derive(Debug, Clone)]
+struct Creation {
+    origin: Vec<Node>,
+    template: Option<usize>,
+}
+
 #[derive(Default, Clone)]
-pub struct State {
+pub struct State<'a> {
     pub position: Vec<Node>,
     pub input: Option<String>,
-    pub style: Style,
-    pub highlight_style: Style,
+    pub(crate) scroll: usize,
+    history: History<'a>,
+    clipboard: Option<Clip<'a>>,
+    system_clipboard: Option<Arc<dyn SystemClipboard>>,
+    system_text: Option<String>,
+    search: Option<search::Search>,
+    key: Option<String>,
+    creation: Option<Creation>,
+    confirm: Option<Vec<Node>>,
+    skip_disabled: bool,
 }
-impl Debug for State {
+impl Debug for State<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("State")
             .field("position", &self.position)
             .field("input", &self.input)
+            .field("clipboard", &self.clipboard)
+            .field("search", &self.search)
+            .field("key", &self.key)
+            .field("creation", &self.creation)
+            .field("confirm", &self.confirm)
+            .field("skip_disabled", &self.skip_disabled)
             .finish()
     }
 }
-impl State {
+impl<'a> State<'a> {
+    pub fn transition(&mut self, event: crate::Event, tree_edit: &mut TreeEdit<'a>) -> Outcome {
+        use crate::Event::*;
+        self.sync(&tree_edit.tabs);
+        let idle = !self.in_input_mode() && !self.in_search_mode() && !self.in_confirm_mode();
+        match event {
+            NewTab | NewBranch if idle => {
+                let Some((name, template)) = tree_edit.templates.first() else {
+                    return Outcome::Rejected {
+                        path: self.position.clone(),
+                        reason: "No templates".into(),
+                    };
+                };
+                let branch = template();
+                let name = name.clone();
+                self.create_handler(&mut tree_edit.tabs, event == NewTab, &name, Some(0), branch)
+            }
+            NextItem | PreviousItem
+                if self
+                    .creation
+                    .as_ref()
+                    .map_or(false, |c| c.template.is_some()) =>
+            {
+                self.template_handler(tree_edit, event == NextItem)
+            }
+            event => self.handle(event, &mut tree_edit.tabs),
+        }
+    }
+
+    pub fn system_clipboard(mut self, clipboard: impl SystemClipboard + 'static) -> Self {
+        self.system_clipboard = Some(Arc::new(clipboard));
+        self
+    }
+    pub fn skip_disabled(mut self, skip: bool) -> Self {
+        self.skip_disabled = skip;
+        self
+    }
+    pub fn get_clipboard(&self) -> Option<&Clip<'a>> {
+        self.clipboard.as_ref()
+    }
+
+    pub fn position(&self) -> &Vec<Node> {
+        &self.position
+    }
+    pub fn select_path(
+        &mut self,
+        tree_edit: &mut TreeEdit<'a>,
+        path: impl IntoPath,
+    ) -> Result<(), PathError> {
+        let (mut nodes, target) = path::walk(&tree_edit.tabs, path)?;
+        if let (path::Target::Branch(branch), [_]) = (target, nodes.as_slice()) {
+            nodes.push(branch.into());
+        }
+        if self.in_input_mode() {
+            self.handle(crate::Event::Cancel, &mut tree_edit.tabs);
+        }
+        if self.in_search_mode() {
+            self.search = None;
+        }
+        for (node, current) in nodes.iter_mut().zip(self.position.iter()) {
+            if node != current {
+                break;
+            }
+            *node = current.clone();
+        }
+        self.position = nodes;
+        Ok(())
+    }
+    pub fn in_input_mode(&self) -> bool {
+        self.input.is_some() || self.in_rename_mode()
+    }
+    pub fn in_rename_mode(&self) -> bool {
+        self.key.is_some()
+    }
+    pub fn in_confirm_mode(&self) -> bool {
+        self.confirm.is_some()
+    }
+    pub fn get_confirmation(&self) -> Option<&Vec<Node>> {
+        self.confirm.as_ref()
+    }
+    pub fn in_create_mode(&self) -> bool {
+        self.creation.is_some()
+    }
+    pub fn get_template<'t>(&self, tree_edit: &'t TreeEdit<'a>) -> Option<&'t String> {
+        let template = self.creation.as_ref()?.template?;
+        tree_edit.templates.get(template).map(|(name, _)| name)
+    }
+    pub fn get_key(&self) -> Option<&String> {
+        self.key.as_ref()
+    }
+    pub fn in_search_mode(&self) -> bool {
+        self.search.as_ref().map_or(false, |search| search.typing)
+    }
+    pub fn search_query(&self) -> Option<&String> {
+        self.search.as_ref().map(|search| &search.query)
+    }
+    pub fn search_matches(&self, tree_edit: &TreeEdit<'a>) -> Vec<Vec<Node>> {
+        self.search_query().map_or_else(Vec::new, |query| {
+            search::candidates(&tree_edit.tabs, query)
+                .into_iter()
+                .filter_map(|(path, matched)| matched.then_some(path))
+                .collect()
+        })
+    }
+    pub fn get_current_tab<'b>(&self, tree_edit: &'b TreeEdit<'a>) -> Option<BranchItem<'a, 'b>> {
+        self.index_tab(&tree_edit.tabs)
+            .and_then(|index| tree_edit.tabs.iter().nth(index))
+    }
+
+    pub(crate) fn context<'s>(
+        &'s self,
+        style: Style,
+        highlight_style: Style,
+        match_style: Style,
+        dirty: &'s [Vec<Node>],
+    ) -> Context<'s> {
+        Context {
+            position: &self.position,
+            input: &self.input,
+            key: self.key.as_ref(),
+            search: self.search_query(),
+            dirty,
+            first_level: self.scroll + 1,
+            style,
+            highlight_style,
+            match_style,
+            access: Default::default(),
+        }
+    }
+
+    pub(crate) fn sync(&mut self, tabs: &crate::Branches) {
+        if self.index_tab(tabs).is_some() {
+            return;
+        }
+        self.input = None;
+        self.key = None;
+        self.creation = None;
+        self.confirm = None;
+        self.position.clear();
+        if let Some((name, branch)) = tabs.front() {
+            self.position.push(Node::tree(name));
+            self.position.push(self.first_item(branch));
+        }
+    }
+
+    fn first_item(&self, branch: &Branch) -> Node {
+        let mut node = Node::from(branch);
+        if self.skip_disabled {
+            let access = |name: &String| match branch {
+                Branch::Args(args) => args.get_value_by_cindex(name, 0).map(|value| {
+                    let nested = value.inner().as_struct().map(Branch::get_access);
+                    value.get_access().max(nested.unwrap_or_default())
+                }),
+                Branch::Tree(tree)
+                | Branch::Array(Array { tree, .. })
+                | Branch::Map(Map { tree, .. }) => {
+                    tree.get_branches().get(name).map(Branch::get_access)
+                }
+            };
+            if let Some(name) = branch
+                .get_list()
+                .into_iter()
+                .find(|name| access(name) != Some(Access::Disabled))
+            {
+                node.change_text(name);
+            }
+        }
+        node
+    }
+
     pub fn index_tab(&self, tabs: &crate::Branches) -> Option<usize> {
         tabs.iter().position(|(tab_name, _)| {
             self.position
-                .get(0)
+                .first()
                 .and_then(|node| node.as_tree().map(|name| name == tab_name))
                 .unwrap_or(false)
         })
@@ -130,24 +354,24 @@ impl State {
         self.position.get(level)
     }
 
-    pub fn current_tab<'a, 'b>(
+    pub fn current_tab<'b>(
         &self,
         tabs: &'b mut crate::Branches<'a>,
     ) -> Option<BranchItemMut<'a, 'b>> {
         self.index_tab(tabs)
             .and_then(|index| tabs.iter_mut().nth(index))
     }
-    fn next_tab<'a, 'b>(&self, tabs: &'b crate::Branches<'a>) -> Option<BranchItem<'a, 'b>> {
+    fn next_tab<'b>(&self, tabs: &'b crate::Branches<'a>) -> Option<BranchItem<'a, 'b>> {
         tabs.iter().nth(self.index_tab(tabs).unwrap_or(0) + 1)
     }
-    fn previous_tab<'a, 'b>(&self, tabs: &'b crate::Branches<'a>) -> Option<BranchItem<'a, 'b>> {
+    fn previous_tab<'b>(&self, tabs: &'b crate::Branches<'a>) -> Option<BranchItem<'a, 'b>> {
         self.index_tab(tabs).and_then(|index| {
             tabs.iter()
                 .nth(if index == 0 { usize::MAX } else { index - 1 })
         })
     }
 
-    fn branch<'a, 'b>(
+    fn branch<'b>(
         &self,
         tabs: &'b mut crate::Branches<'a>,
         offset: usize,
@@ -160,55 +384,100 @@ impl State {
         })
     }
     #[inline]
-    fn current_branch<'a, 'b>(
-        &self,
-        tabs: &'b mut crate::Branches<'a>,
-    ) -> Option<&'b mut Branch<'a>> {
+    fn current_branch<'b>(&self, tabs: &'b mut crate::Branches<'a>) -> Option<&'b mut Branch<'a>> {
         self.branch(tabs, 0)
     }
 
-    fn next_item_impl<'a>(&self, iter: impl Iterator<Item = &'a String>) -> Option<String> {
-        iter.skip_while(|&name| {
-            self.position
-                .last()
-                .map_or(true, |node| *name != *node.text())
-        })
-        .nth(1)
-        .cloned()
+    fn next_item_impl(&self, tabs: &mut crate::Branches<'a>, list: Vec<String>) -> Option<String> {
+        let mut path = self.position.clone();
+        list.into_iter()
+            .skip_while(|name| {
+                self.position
+                    .last()
+                    .map_or(true, |node| *name != *node.text())
+            })
+            .skip(1)
+            .find(|name| {
+                !self.skip_disabled || {
+                    if let Some(node) = path.last_mut() {
+                        node.change_text(name.clone());
+                    }
+                    Self::access_at(tabs, &path) != Access::Disabled
+                }
+            })
     }
-    fn next_item(&self, tabs: &mut crate::Branches) -> Option<String> {
-        self.current_branch(tabs)
-            .and_then(|branch| self.next_item_impl(branch.get_list().iter()))
+    fn next_item(&self, tabs: &mut crate::Branches<'a>) -> Option<String> {
+        let list = self.current_branch(tabs)?.get_list();
+        self.next_item_impl(tabs, list)
     }
-    fn previous_item(&self, tabs: &mut crate::Branches) -> Option<String> {
-        self.current_branch(tabs)
-            .and_then(|branch| self.next_item_impl(branch.get_list().iter().rev()))
+    fn previous_item(&self, tabs: &mut crate::Branches<'a>) -> Option<String> {
+        let list = self.current_branch(tabs)?.get_list();
+        self.next_item_impl(tabs, list.into_iter().rev().collect())
     }
 
-    fn value<'a, 'b>(
-        &self,
+    pub(crate) fn value_at<'b>(
         tabs: &'b mut crate::Branches<'a>,
-        offset: usize,
+        path: &[Node],
     ) -> Option<&'b mut Value<'a>> {
-        self.branch(tabs, offset)
+        let (tab, nodes) = path.split_first()?;
+        let (node, nodes) = nodes.split_last()?;
+        let (name, column, _) = node.as_args()?;
+        tabs.get_mut(tab.as_tree()?)
+            .and_then(|branch| Self::incise_position(branch, nodes.iter()))
             .and_then(|branch| branch.as_args_mut())
-            .zip(
-                self.position
-                    .iter()
-                    .nth_back(offset)
-                    .and_then(|node| node.as_args()),
-            )
-            .and_then(|(args, (name, column, _))| args.get_value_by_cindex_mut(name, *column))
+            .and_then(|args| args.get_value_by_cindex_mut(name, *column))
     }
-    #[inline]
-    fn current_value<'a, 'b>(
+    pub(crate) fn branch_at<'b>(
+        tabs: &'b mut crate::Branches<'a>,
+        path: &[Node],
+    ) -> Option<&'b mut Branch<'a>> {
+        let (tab, nodes) = path.split_first()?;
+        tabs.get_mut(tab.as_tree()?)
+            .and_then(|branch| Self::incise_position(branch, nodes.iter()))
+    }
+    pub(crate) fn branches_at<'b>(
+        tabs: &'b mut crate::Branches<'a>,
+        path: &[Node],
+    ) -> Option<&'b mut crate::Branches<'a>> {
+        if path.is_empty() {
+            return Some(tabs);
+        }
+        match Self::branch_at(tabs, path)? {
+            Branch::Tree(tree) | Branch::Map(Map { tree, .. }) => Some(tree.get_branches_mut()),
+            Branch::Args(_) | Branch::Array(_) => None,
+        }
+    }
+    pub(crate) fn access_at(tabs: &mut crate::Branches<'a>, path: &[Node]) -> Access {
+        (1..=path.len())
+            .filter_map(|len| {
+                let path = &path[..len];
+                let value = path
+                    .last()
+                    .filter(|node| node.is_args())
+                    .and_then(|_| Self::value_at(tabs, path))
+                    .map(|value| value.get_access());
+                let branch = Self::branch_at(tabs, path).map(|branch| branch.get_access());
+                value.max(branch)
+            })
+            .max()
+            .unwrap_or_default()
+    }
+    fn value<'b>(
         &self,
         tabs: &'b mut crate::Branches<'a>,
+        offset: usize,
     ) -> Option<&'b mut Value<'a>> {
+        Self::value_at(
+            tabs,
+            &self.position[..self.position.len().saturating_sub(offset)],
+        )
+    }
+    #[inline]
+    fn current_value<'b>(&self, tabs: &'b mut crate::Branches<'a>) -> Option<&'b mut Value<'a>> {
         self.value(tabs, 0)
     }
 
-    fn incise_position<'a, 'b, 'n>(
+    fn incise_position<'b, 'n>(
         branch: &'b mut Branch<'a>,
         mut nodes: impl Iterator<Item = &'n Node>,
     ) -> Option<&'b mut Branch<'a>> {
@@ -219,10 +488,10 @@ impl State {
                         node.text(),
                         node.as_args().map_or(usize::MAX, |(_, index, _)| *index),
                     )
-                    .and_then(|value| value.as_struct_mut()),
-                Branch::Tree(tree) | Branch::Array(Array { tree, .. }) => {
-                    tree.get_branches_mut().get_mut(node.text())
-                }
+                    .and_then(|value| value.inner_mut().as_struct_mut()),
+                Branch::Tree(tree)
+                | Branch::Array(Array { tree, .. })
+                | Branch::Map(Map { tree, .. }) => tree.get_branches_mut().get_mut(node.text()),
             } {
                 Self::incise_position(branch, nodes)
             } else {
@@ -233,26 +502,43 @@ impl State {
         }
     }
 
-    pub fn transition(&mut self, event: crate::Event, tabs: &mut crate::Branches) {
-        if self.input.is_some() {
-            self.enter_handler(tabs, event);
-            return;
+    fn handle(&mut self, event: crate::Event, tabs: &mut crate::Branches<'a>) -> Outcome {
+        use crate::Event::*;
+        if self.in_search_mode() {
+            return self.search_handler(tabs, event);
+        }
+        if let Some(path) = self.confirm.take() {
+            return match event {
+                Enter => self.remove_handler(tabs, path),
+                _ => Outcome::Cancelled { path },
+            };
+        }
+        if self.in_input_mode() {
+            let event = match event {
+                Paste => match self.clipboard_text() {
+                    Some(text) => InsertStr(text),
+                    None => return Outcome::None,
+                },
+                event => event,
+            };
+            if self.in_rename_mode() {
+                return self.rename_handler(tabs, event);
+            }
+            return self.enter_handler(tabs, event);
         }
 
-        use crate::Event::*;
         match event {
-            NextTab | PreviousTab => self.tab_handler(tabs, event == NextTab),
+            NextTab | PreviousTab => return self.tab_handler(tabs, event == NextTab),
             NextItem | PreviousItem => self.item_handler(tabs, event == NextItem),
-            NextLevel | Enter => {
-                if event == Enter {
-                    if self.enter_handler(tabs, Enter) {
-                        self.next_level_handler(tabs, event == NextLevel)
-                    }
-                } else {
-                    self.next_level_handler(tabs, event == NextLevel)
+            NextLevel => self.next_level_handler(tabs, true),
+            Enter => {
+                let outcome = self.enter_handler(tabs, Enter);
+                if !matches!(outcome, Outcome::Inserted { .. }) && !self.in_rename_mode() {
+                    self.next_level_handler(tabs, false)
                 }
+                return outcome;
             }
-            PreviousLevel => {
+            PreviousLevel
                 if (self.position.len() > 2
                     || self
                         .position
@@ -264,53 +550,911 @@ impl State {
                         .current_branch(tabs)
                         .and_then(|branch| match branch {
                             Branch::Args(_) => Some(()),
-                            Branch::Tree(_) | Branch::Array(_) => None,
+                            Branch::Tree(_) | Branch::Array(_) | Branch::Map(_) => None,
                         })
                         .map_or(Some(()), |_| {
                             self.position
                                 .last_mut()
                                 .and_then(|node| node.dec_index().then_some(()))
                         })
-                        .is_some()
-                {
-                    self.position.pop();
+                        .is_some() =>
+            {
+                self.position.pop();
+            }
+            InsertBefore | InsertAfter | Duplicate | MoveUp | MoveDown => {
+                return self.array_handler(tabs, event)
+            }
+            Rename => return self.start_rename(tabs),
+            CloneTab | CloneBranch => {
+                let is_tab = event == CloneTab;
+                let path = self.target(is_tab);
+                let Some(branch) = Self::branch_at(tabs, &path).cloned() else {
+                    return Outcome::None;
+                };
+                let name = path.last().map(Node::text).cloned().unwrap_or_default();
+                return self.create_handler(tabs, is_tab, &name, None, branch);
+            }
+            RemoveTab | RemoveBranch => {
+                let path = self.target(event == RemoveTab);
+                if let Err(reason) = self.check_structure(tabs, &path) {
+                    return Outcome::Rejected { path, reason };
+                }
+                self.confirm = Some(path.clone());
+                return Outcome::Confirm { path };
+            }
+            Copy | CopyRow => return self.copy_handler(tabs, event == CopyRow),
+            Cut | CutRow => return self.cut_handler(tabs, event == CutRow),
+            Paste => return self.paste_handler(tabs),
+            Search => {
+                self.search = Some(search::Search {
+                    typing: true,
+                    origin: self.position.clone(),
+                    ..Default::default()
+                })
+            }
+            NextMatch | PreviousMatch => {
+                return self.search_jump(tabs, Some(event == NextMatch));
+            }
+            Cancel => self.search = None,
+            Undo | Redo => {
+                if let Some((position, outcome)) = if event == Undo {
+                    self.history.undo(tabs)
+                } else {
+                    self.history.redo(tabs)
+                } {
+                    self.position = position;
+                    return outcome;
                 }
             }
             Delete => {
-                let Some(node) = self.position.last().map(|n| n.text()) else {
-                    return;
+                let Some(node) = self.position.last().map(|n| n.text().clone()) else {
+                    return Outcome::None;
+                };
+                let access = Self::access_at(tabs, &self.position);
+                let rejected = Outcome::Rejected {
+                    path: self.position.clone(),
+                    reason: access.to_string(),
                 };
 
+                if let Some(value) = self.current_value(tabs).filter(|v| v.is_option()) {
+                    if access != Access::Editable {
+                        return rejected;
+                    }
+                    let old = value.clone();
+                    if value.clear() {
+                        let path = self.position.clone();
+                        self.history.record(Change::Replace {
+                            path: path.clone(),
+                            old,
+                            new: value.clone(),
+                        });
+                        return Outcome::Cleared { path };
+                    }
+                }
+
                 if let Some(array) = self
                     .value(tabs, 1)
-                    .and_then(|v| v.as_array_mut())
+                    .and_then(|v| v.inner_mut().as_array_mut())
                     .filter(|a| !a.get_branches().is_empty())
                 {
-                    if let Some(current) = array.remove(node) {
+                    if access != Access::Editable {
+                        return rejected;
+                    }
+                    if let Err(reason) = array.check_remove() {
+                        return Outcome::Rejected {
+                            path: self.position.clone(),
+                            reason,
+                        };
+                    }
+                    let index = node.parse().unwrap_or_default();
+                    let path = self.position[..self.position.len() - 1].to_vec();
+                    if let Some(element) = array.element(index).cloned() {
+                        self.history.record(Change::Remove {
+                            path: path.clone(),
+                            index,
+                            element,
+                        });
+                    }
+                    let current = array.remove(&node);
+                    if let Some(current) = current {
                         *self.position.last_mut().map(|n| n.text_mut()).unwrap() = current
                     } else {
                         self.position.pop();
                     }
+                    return Outcome::Removed { path, index };
+                }
+
+                if let Some(value) = self
+                    .value(tabs, 1)
+                    .filter(|v| v.inner().is_map())
+                    .filter(|_| self.position.last().map_or(false, Node::is_tree))
+                {
+                    if access != Access::Editable {
+                        return rejected;
+                    }
+                    let path = self.position[..self.position.len() - 1].to_vec();
+                    let Some(map) = value.inner_mut().as_map_mut() else {
+                        return Outcome::None;
+                    };
+                    let Some((index, branch)) = map
+                        .get_branches()
+                        .iter()
+                        .enumerate()
+                        .find(|(_, (key, _))| **key == node)
+                        .map(|(index, (_, branch))| (index, branch.clone()))
+                    else {
+                        return Outcome::None;
+                    };
+                    self.history.record(Change::Detach {
+                        path: self.position.clone(),
+                        index,
+                        branch,
+                    });
+                    match map.remove(&node) {
+                        Some(current) => {
+                            *self.position.last_mut().map(|n| n.text_mut()).unwrap() = current
+                        }
+                        None => {
+                            self.position.pop();
+                        }
+                    }
+                    return Outcome::Removed { path, index };
+                }
+            }
+            _ => (),
+        }
+        Outcome::None
+    }
+
+    fn array_handler(&mut self, tabs: &mut crate::Branches<'a>, event: crate::Event) -> Outcome {
+        use crate::Event::*;
+        let Some(index) = self
+            .position
+            .last()
+            .filter(|node| node.is_tree())
+            .and_then(|node| node.text().parse::<usize>().ok())
+        else {
+            return Outcome::None;
+        };
+        let access = Self::access_at(tabs, &self.position);
+        let Some(array) = self
+            .value(tabs, 1)
+            .and_then(|v| v.inner_mut().as_array_mut())
+        else {
+            return Outcome::None;
+        };
+        if access != Access::Editable {
+            return Outcome::Rejected {
+                path: self.position.clone(),
+                reason: access.to_string(),
+            };
+        }
+
+        if let (InsertBefore | InsertAfter | Duplicate, Err(reason)) =
+            (&event, array.check_insert())
+        {
+            return Outcome::Rejected {
+                path: self.position.clone(),
+                reason,
+            };
+        }
+
+        let path = self.position[..self.position.len() - 1].to_vec();
+        let change = match event {
+            InsertBefore => Change::Insert {
+                path,
+                index,
+                element: array.prototype(),
+            },
+            InsertAfter => Change::Insert {
+                path,
+                index: index + 1,
+                element: array.prototype(),
+            },
+            Duplicate => match array.element(index) {
+                Some(element) => Change::Insert {
+                    path,
+                    index: index + 1,
+                    element: element.clone(),
+                },
+                None => return Outcome::None,
+            },
+            MoveUp if index > 0 => Change::Move {
+                path,
+                from: index,
+                to: index - 1,
+            },
+            MoveDown => Change::Move {
+                path,
+                from: index,
+                to: index + 1,
+            },
+            _ => return Outcome::None,
+        };
+        let Some((position, outcome)) = change.apply(tabs) else {
+            return Outcome::None;
+        };
+        self.history.record(change);
+        self.position = position;
+        outcome
+    }
+
+    fn target(&self, is_tab: bool) -> Vec<Node> {
+        let len = if is_tab { 1 } else { self.position.len() };
+        self.position.iter().take(len).cloned().collect()
+    }
+
+    fn check_structure(&self, tabs: &mut crate::Branches<'a>, path: &[Node]) -> Result<(), String> {
+        let Some((node, parent)) = path.split_last() else {
+            return Err("Nothing selected".into());
+        };
+        let access = Self::access_at(tabs, path);
+        if access != Access::Editable {
+            return Err(access.to_string());
+        }
+        Self::branches_at(tabs, parent)
+            .filter(|branches| branches.contains_key(node.text()))
+            .map(|_| ())
+            .ok_or_else(|| "Not a tree branch".into())
+    }
+
+    fn create_handler(
+        &mut self,
+        tabs: &mut crate::Branches<'a>,
+        is_tab: bool,
+        base: &str,
+        template: Option<usize>,
+        branch: Branch<'a>,
+    ) -> Outcome {
+        let origin = self.position.clone();
+        let path = self.target(is_tab);
+        let (current, parent) = match path.split_last() {
+            Some((node, parent)) => (Some(node.text()), parent),
+            None if is_tab => (None, &path[..]),
+            None => return Outcome::None,
+        };
+        let access = Self::access_at(tabs, parent);
+        if access != Access::Editable {
+            return Outcome::Rejected {
+                path,
+                reason: access.to_string(),
+            };
+        }
+        if let Some(Branch::Map(map)) = Self::branch_at(tabs, parent) {
+            if !map.prototype().same_shape(&branch) {
+                return Outcome::Rejected {
+                    path,
+                    reason: "Entry layout mismatch".into(),
+                };
+            }
+        }
+        let Some(branches) = Self::branches_at(tabs, parent) else {
+            return Outcome::Rejected {
+                path,
+                reason: "Not a tree branch".into(),
+            };
+        };
+        let index = current
+            .and_then(|current| branches.keys().position(|name| name == current))
+            .map_or(branches.len(), |index| index + 1);
+        let name = branch::unique_name(branches, base);
+        branch::insert(branches, index, name.clone(), branch);
+
+        self.position = parent.to_vec();
+        self.position.push(Node::tree(&name));
+        self.key = Some(name);
+        self.creation = Some(Creation { origin, template });
+        Outcome::Creating {
+            path: self.position.clone(),
+            template: template.map(|_| base.to_string()),
+        }
+    }
+
+    fn template_handler(&mut self, tree_edit: &mut TreeEdit<'a>, is_next: bool) -> Outcome {
+        let len = tree_edit.templates.len();
+        let Some(template) = self.creation.as_mut().and_then(|c| c.template.as_mut()) else {
+            return Outcome::None;
+        };
+        *template = if is_next {
+            (*template + 1) % len
+        } else {
+            (*template + len - 1) % len
+        };
+        let (name, template) = &tree_edit.templates[*template];
+        let branch = template();
+        let name = name.clone();
+        if let Some(current) = self.position.split_last().and_then(|(node, parent)| {
+            Self::branches_at(&mut tree_edit.tabs, parent)?.get_mut(node.text())
+        }) {
+            *current = branch;
+        }
+        Outcome::Creating {
+            path: self.position.clone(),
+            template: Some(name),
+        }
+    }
+
+    fn remove_handler(&mut self, tabs: &mut crate::Branches<'a>, path: Vec<Node>) -> Outcome {
+        let Some((node, parent)) = path.split_last() else {
+            return Outcome::None;
+        };
+        let Some((index, branch)) = Self::branches_at(tabs, parent).and_then(|branches| {
+            branches
+                .iter()
+                .enumerate()
+                .find(|(_, (name, _))| *name == node.text())
+                .map(|(index, (_, branch))| (index, branch.clone()))
+        }) else {
+            return Outcome::None;
+        };
+        let change = Change::Detach {
+            path,
+            index,
+            branch,
+        };
+        let Some((position, outcome)) = change.apply(tabs) else {
+            return Outcome::None;
+        };
+        self.history.record(change);
+        self.position = position;
+        outcome
+    }
+
+    fn check_rename(
+        tabs: &mut crate::Branches<'a>,
+        path: &[Node],
+        new: &str,
+        force: bool,
+    ) -> Result<(), String> {
+        let Some((node, parent)) = path.split_last() else {
+            return Err("Not renamable".into());
+        };
+        let old = node.text();
+        let unique = |taken: bool| {
+            if new.is_empty() {
+                Err("Empty name".into())
+            } else if new != old && taken {
+                Err(format!("Name \"{new}\" already exists"))
+            } else {
+                Ok(())
+            }
+        };
+        if parent.is_empty() {
+            return if force {
+                unique(tabs.contains_key(new))
+            } else {
+                Err("Not renamable".into())
+            };
+        }
+        match Self::branch_at(tabs, parent) {
+            Some(Branch::Map(map)) => map.check_key(old, new),
+            Some(Branch::Tree(tree)) if force || tree.is_renamable() => {
+                unique(tree.get_branches().contains_key(new))
+            }
+            Some(Branch::Args(args)) if args.is_renamable() && node.is_args() => {
+                unique(args.get_names_raw().iter().any(|name| name == new))
+            }
+            _ => Err("Not renamable".into()),
+        }
+    }
+
+    fn start_rename(&mut self, tabs: &mut crate::Branches<'a>) -> Outcome {
+        let path = self.position.clone();
+        let Some(name) = path.last().map(Node::text) else {
+            return Outcome::None;
+        };
+        let access = Self::access_at(tabs, &path);
+        if access != Access::Editable {
+            return Outcome::Rejected {
+                reason: access.to_string(),
+                path,
+            };
+        }
+        if let Err(reason) = Self::check_rename(tabs, &path, name, false) {
+            return Outcome::Rejected { path, reason };
+        }
+        self.key = Some(name.clone());
+        Outcome::None
+    }
+
+    fn rename_handler(&mut self, tabs: &mut crate::Branches<'a>, event: crate::Event) -> Outcome {
+        use crate::Event::*;
+        let (Some(key), Some(old)) = (self.key.as_mut(), self.position.last().map(Node::text))
+        else {
+            return Outcome::None;
+        };
+        let path = self.position.clone();
+        match event {
+            Char(sym) => key.push(sym),
+            InsertStr(text) => key.extend(text.chars().filter(|sym| !sym.is_control())),
+            Backspace => {
+                key.pop();
+            }
+            DeleteWord => {
+                let len = key.trim_end().len();
+                key.truncate(key[..len].rfind(' ').map_or(0, |index| index + 1));
+            }
+            Cancel => {
+                self.key = None;
+                let Some(creation) = self.creation.take() else {
+                    return Outcome::Cancelled { path };
+                };
+                if let Some((branches, node)) = path
+                    .split_last()
+                    .and_then(|(node, parent)| Some((Self::branches_at(tabs, parent)?, node)))
+                {
+                    branches.remove(node.text());
+                }
+                self.position = creation.origin;
+                return Outcome::Cancelled { path };
+            }
+            Enter if self.creation.is_some() => {
+                let new = key.clone();
+                if let Err(reason) = Self::check_rename(tabs, &path, &new, true) {
+                    return Outcome::Rejected { path, reason };
+                }
+                let Some((node, parent)) = path.split_last() else {
+                    return Outcome::None;
+                };
+                let Some(branches) = Self::branches_at(tabs, parent) else {
+                    return Outcome::None;
+                };
+                branch::rekey(branches, node.text(), &new);
+                let Some((index, branch)) = branches
+                    .iter()
+                    .enumerate()
+                    .find(|(_, (name, _))| **name == new)
+                    .map(|(index, (_, branch))| (index, branch.clone()))
+                else {
+                    return Outcome::None;
+                };
+                let mut path = parent.to_vec();
+                path.push(Node::tree(&new));
+                self.position = path.clone();
+                if parent.is_empty() {
+                    self.position.push(Node::from(&branch));
+                }
+                self.history.record(Change::Attach {
+                    path: path.clone(),
+                    index,
+                    branch,
+                });
+                self.key = None;
+                self.creation = None;
+                return Outcome::Created { path };
+            }
+            Enter if key == old => self.key = None,
+            Enter => {
+                let new = key.clone();
+                if let Err(reason) = Self::check_rename(tabs, &path, &new, false) {
+                    return Outcome::Rejected { path, reason };
                 }
+                let change = Change::Rename { path, new };
+                let Some((position, outcome)) = change.apply(tabs) else {
+                    return Outcome::None;
+                };
+                self.history.record(change);
+                self.key = None;
+                self.position = position;
+                return outcome;
             }
             _ => (),
         }
+        Outcome::None
+    }
+
+    fn clipboard_text(&self) -> Option<String> {
+        self.system_clipboard
+            .as_ref()
+            .and_then(|system| system.get())
+            .or_else(|| self.clipboard.as_ref().and_then(Clip::text))
+    }
+
+    fn clip(&mut self, tabs: &mut crate::Branches<'a>, row: bool) -> Option<Clip<'a>> {
+        match self.position.last()?.as_args() {
+            Some((name, _, _)) if row => self
+                .current_branch(tabs)
+                .and_then(|branch| branch.as_args())
+                .and_then(|args| {
+                    let row = args.get_names_raw().iter().position(|n| n == name)?;
+                    Some(Clip::Row(
+                        (0..args.get_columns_raw().len())
+                            .map(|column| args.get_value_by_indexes(row, column).cloned())
+                            .collect(),
+                    ))
+                }),
+            Some(_) => self
+                .current_value(tabs)
+                .map(|value| Clip::Value(value.clone())),
+            None => {
+                Self::branch_at(tabs, &self.position).map(|branch| Clip::Branch(branch.clone()))
+            }
+        }
+    }
+
+    fn keep(&mut self, clip: Clip<'a>) {
+        if let Some(system) = &self.system_clipboard {
+            if let Some(text) = clip.text() {
+                system.set(text);
+            }
+            self.system_text = system.get();
+        }
+        self.clipboard = Some(clip);
+    }
+
+    fn copy_handler(&mut self, tabs: &mut crate::Branches<'a>, row: bool) -> Outcome {
+        let Some(clip) = self.clip(tabs, row) else {
+            return Outcome::None;
+        };
+        self.keep(clip);
+        Outcome::Copied {
+            path: self.position.clone(),
+        }
     }
 
-    fn tab_handler(&mut self, tabs: &mut crate::Branches, is_next: bool) {
-        if let Some((name, branch)) = if is_next {
+    fn cut_handler(&mut self, tabs: &mut crate::Branches<'a>, row: bool) -> Outcome {
+        let Some(clip) = self.clip(tabs, row) else {
+            return Outcome::None;
+        };
+        let path = self.position.clone();
+        let outcome = if path.last().map_or(false, Node::is_args) {
+            self.clear_handler(tabs, row)
+        } else {
+            match self.handle(crate::Event::Delete, tabs) {
+                Outcome::None => match self.check_structure(tabs, &path) {
+                    Ok(()) => self.remove_handler(tabs, path),
+                    Err(reason) => Outcome::Rejected { path, reason },
+                },
+                outcome => outcome,
+            }
+        };
+        if !matches!(outcome, Outcome::None | Outcome::Rejected { .. }) {
+            self.keep(clip);
+        }
+        outcome
+    }
+
+    fn clear_handler(&mut self, tabs: &mut crate::Branches<'a>, row: bool) -> Outcome {
+        let path = self.position.clone();
+        let rejected = |reason: String| Outcome::Rejected {
+            path: path.clone(),
+            reason,
+        };
+        let access = Self::access_at(tabs, &path);
+        if access != Access::Editable {
+            return rejected(access.to_string());
+        }
+        let Some((name, column, _)) = path.last().and_then(Node::as_args) else {
+            return Outcome::None;
+        };
+        let Some(args) = self
+            .current_branch(tabs)
+            .and_then(|branch| branch.as_args())
+        else {
+            return Outcome::None;
+        };
+        let Some(index) = args.get_names_raw().iter().position(|n| n == name) else {
+            return Outcome::None;
+        };
+        let columns = if row {
+            0..args.get_columns_raw().len()
+        } else {
+            *column..*column + 1
+        };
+        let mut changes = Vec::new();
+        for column in columns {
+            let Some(value) = args.get_value_by_indexes(index, column) else {
+                continue;
+            };
+            if value.get_access() != Access::Editable {
+                return rejected(value.get_access().to_string());
+            }
+            let new = match value.cleared() {
+                Ok(new) => new,
+                Err(reason) => return rejected(reason),
+            };
+            let mut path = path[..path.len() - 1].to_vec();
+            path.push(Node::args(name, column));
+            changes.push(Change::Replace {
+                path,
+                old: value.clone(),
+                new,
+            });
+        }
+        let change = Change::Group(changes);
+        change.apply(tabs);
+        self.history.record(change);
+        Outcome::Cleared { path }
+    }
+
+    fn paste_handler(&mut self, tabs: &mut crate::Branches<'a>) -> Outcome {
+        let path = self.position.clone();
+        let rejected = |reason: String| Outcome::Rejected {
+            path: path.clone(),
+            reason,
+        };
+        let access = Self::access_at(tabs, &path);
+        if access != Access::Editable {
+            return rejected(access.to_string());
+        }
+        let external = self
+            .system_clipboard
+            .as_ref()
+            .and_then(|system| system.get())
+            .filter(|text| self.system_text.as_ref() != Some(text));
+        if let Some(text) = external {
+            return self.paste_text(tabs, &text);
+        }
+        let Some(clip) = self.clipboard.clone() else {
+            return Outcome::None;
+        };
+        let is_args = path.last().map_or(false, Node::is_args);
+
+        let change = match clip {
+            Clip::Value(clip) if is_args => {
+                let Some(value) = self.current_value(tabs) else {
+                    return Outcome::None;
+                };
+                let old = value.clone();
+                if !value.assign(&clip) {
+                    return match clip.text() {
+                        Some(text) => self.paste_text(tabs, &text),
+                        None => rejected(format!("Expected {}", old.get_type())),
+                    };
+                }
+                if let Err(reason) = value.validate() {
+                    *value = old;
+                    return rejected(reason);
+                }
+                Change::Replace {
+                    path: path.clone(),
+                    old,
+                    new: value.clone(),
+                }
+            }
+            Clip::Row(clip) if is_args => {
+                let Some((name, _, _)) = path.last().and_then(Node::as_args) else {
+                    return Outcome::None;
+                };
+                let Some(args) = self
+                    .current_branch(tabs)
+                    .and_then(|branch| branch.as_args_mut())
+                else {
+                    return Outcome::None;
+                };
+                let Some(row) = args.get_names_raw().iter().position(|n| n == name) else {
+                    return Outcome::None;
+                };
+                if clip.len() != args.get_columns_raw().len() {
+                    return rejected("Column count mismatch".into());
+                }
+                let mut cells = Vec::new();
+                for (column, clip) in clip.iter().enumerate() {
+                    let (Some(clip), Some(value)) = (clip, args.get_value_by_indexes(row, column))
+                    else {
+                        continue;
+                    };
+                    if value.get_access() != Access::Editable {
+                        return rejected(value.get_access().to_string());
+                    }
+                    let mut new = value.clone();
+                    if !new.assign(clip) {
+                        return rejected(format!("Expected {}", value.get_type()));
+                    }
+                    if let Err(reason) = new.validate() {
+                        return rejected(reason);
+                    }
+                    cells.push((column, value.clone(), new));
+                }
+                let name = name.clone();
+                Change::Group(
+                    cells
+                        .into_iter()
+                        .map(|(column, old, new)| {
+                            if let Some(value) = args.get_value_by_indexes_mut(row, column) {
+                                *value = new.clone();
+                            }
+                            let mut path = path[..path.len() - 1].to_vec();
+                            path.push(Node::args(&name, column));
+                            Change::Replace { path, old, new }
+                        })
+                        .collect(),
+                )
+            }
+            Clip::Branch(clip) if is_args => {
+                let Some(value) = self.current_value(tabs) else {
+                    return Outcome::None;
+                };
+                let old = value.clone();
+                if !value.assign(&clip.into()) {
+                    return rejected(format!("Expected {}", old.get_type()));
+                }
+                if let Err(reason) = value.validate() {
+                    *value = old;
+                    return rejected(reason);
+                }
+                Change::Replace {
+                    path: path.clone(),
+                    old,
+                    new: value.clone(),
+                }
+            }
+            Clip::Branch(clip) => match self.current_branch(tabs) {
+                Some(Branch::Array(array)) => {
+                    if !array.prototype().same_shape(&clip) {
+                        return rejected("Element layout mismatch".into());
+                    }
+                    if let Err(reason) = array.check_insert() {
+                        return rejected(reason);
+                    }
+                    let index = path
+                        .last()
+                        .and_then(|node| node.text().parse::<usize>().ok())
+                        .map_or(0, |index| index + 1);
+                    array.insert(Some(index), Value::from(clip.clone()));
+                    let array_path = path[..path.len() - 1].to_vec();
+                    self.position.pop();
+                    self.position.push(Node::tree(index));
+                    self.history.record(Change::Insert {
+                        path: array_path,
+                        index,
+                        element: clip,
+                    });
+                    return Outcome::Pasted {
+                        path: self.position.clone(),
+                    };
+                }
+                Some(_) => {
+                    let Some(branch) = Self::branch_at(tabs, &path) else {
+                        return Outcome::None;
+                    };
+                    if !branch.same_shape(&clip) {
+                        return rejected("Branch layout mismatch".into());
+                    }
+                    let old = std::mem::replace(branch, clip.clone());
+                    Change::Swap {
+                        path: path.clone(),
+                        old,
+                        new: clip,
+                    }
+                }
+                None => return Outcome::None,
+            },
+            _ => return rejected("Nothing to paste here".into()),
+        };
+        self.history.record(change);
+        Outcome::Pasted { path }
+    }
+
+    fn paste_text(&mut self, tabs: &mut crate::Branches<'a>, text: &str) -> Outcome {
+        let path = self.position.clone();
+        let Some(value) = self
+            .current_value(tabs)
+            .filter(|_| path.last().map_or(false, Node::is_args))
+        else {
+            return Outcome::Rejected {
+                path,
+                reason: "Nothing to paste here".into(),
+            };
+        };
+        let old = value.clone();
+        value.assign_default();
+        let parsed = match value.inner_mut().as_bool_mut() {
+            Some(flag) => text
+                .trim()
+                .to_lowercase()
+                .parse()
+                .map(|new| *flag = new)
+                .is_ok(),
+            None => {
+                value.set_text(text);
+                value.inner().text().as_deref() == Some(text)
+            }
+        };
+        let result = if parsed {
+            value.validate()
+        } else {
+            Err(format!("Expected {}", old.get_type()))
+        };
+        if let Err(reason) = result {
+            *value = old;
+            return Outcome::Rejected { path, reason };
+        }
+        self.history.record(Change::Replace {
+            path: path.clone(),
+            old,
+            new: value.clone(),
+        });
+        Outcome::Pasted { path }
+    }
+
+    fn search_handler(&mut self, tabs: &mut crate::Branches<'a>, event: crate::Event) -> Outcome {
+        use crate::Event::*;
+        let Some(search) = self.search.as_mut() else {
+            return Outcome::None;
+        };
+        match event {
+            Char(sym) => search.query.push(sym),
+            InsertStr(text) => search.query.push_str(&text),
+            Backspace => {
+                search.query.pop();
+            }
+            NextItem | NextMatch => return self.search_jump(tabs, Some(true)),
+            PreviousItem | PreviousMatch => return self.search_jump(tabs, Some(false)),
+            Enter => {
+                search.typing = false;
+                if search.query.is_empty() {
+                    self.search = None;
+                }
+                return Outcome::None;
+            }
+            Cancel => {
+                self.position = std::mem::take(&mut search.origin);
+                self.search = None;
+                return Outcome::None;
+            }
+            _ => return Outcome::None,
+        }
+        self.position = search.origin.clone();
+        self.search_jump(tabs, None)
+    }
+
+    fn search_jump(&mut self, tabs: &crate::Branches<'a>, is_next: Option<bool>) -> Outcome {
+        let Some(query) = self.search_query() else {
+            return Outcome::None;
+        };
+        let mut candidates = search::candidates(tabs, query);
+        let matches = candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (_, matched))| matched.then_some(index))
+            .collect::<Vec<_>>();
+        if matches.is_empty() {
+            return Outcome::None;
+        }
+
+        let current = candidates
+            .iter()
+            .position(|(path, _)| *path == self.position);
+        let index = match (is_next, current) {
+            (None, Some(current)) => matches.iter().position(|&index| index >= current),
+            (Some(true), Some(current)) => matches.iter().position(|&index| index > current),
+            (Some(false), Some(current)) => matches.iter().rposition(|&index| index < current),
+            (_, None) => None,
+        }
+        .unwrap_or(if is_next == Some(false) {
+            matches.len() - 1
+        } else {
+            0
+        });
+
+        self.position = candidates.swap_remove(matches[index]).0;
+        Outcome::Found {
+            path: self.position.clone(),
+            index,
+            count: matches.len(),
+        }
+    }
+
+    fn tab_handler(&mut self, tabs: &mut crate::Branches<'a>, is_next: bool) -> Outcome {
+        let Some((name, branch)) = (if is_next {
             self.next_tab(tabs).or_else(|| tabs.front())
         } else {
             self.previous_tab(tabs).or_else(|| tabs.back())
-        } {
-            self.position.clear();
-            self.position.push(Node::Tree(name.clone()));
-            self.position.push(branch.into())
+        }) else {
+            return Outcome::None;
         };
-        self.transition(crate::Event::PreviousItem, tabs)
+
+        let name = name.clone();
+        let item = self.first_item(branch);
+        self.position.clear();
+        self.position.push(Node::tree(&name));
+        self.position.push(item);
+        self.handle(crate::Event::PreviousItem, tabs);
+        Outcome::TabSwitched(name)
     }
 
-    fn item_handler(&mut self, tabs: &mut crate::Branches, is_next: bool) {
+    fn item_handler(&mut self, tabs: &mut crate::Branches<'a>, is_next: bool) {
         if is_next {
             self.next_item(tabs)
         } else {
@@ -319,7 +1463,8 @@ impl State {
         .map(|text| self.position.last_mut().map(|node| node.change_text(text)));
     }
 
-    fn next_level_handler(&mut self, tabs: &mut crate::Branches, is_next: bool) {
+    fn next_level_handler(&mut self, tabs: &mut crate::Branches<'a>, is_next: bool) {
+        let disabled = Self::access_at(tabs, &self.position) == Access::Disabled;
         let Some(branch) = self.current_branch(tabs) else {
             return;
         };
@@ -351,14 +1496,18 @@ impl State {
                             .flatten();
                         args.get_value_by_indexes(name, index.unwrap_or(0))
                     })
+                    .map(Value::inner)
                     .and_then(|v| {
                         v.as_struct()
                             .filter(|_| v.as_array().map_or(true, |a| !a.branches.is_empty()))
-                            .map(Into::into)
+                            .filter(|_| v.as_map().map_or(true, |m| !m.branches.is_empty()))
+                            .map(|branch| self.first_item(branch))
                     })
                     .map(|node| (node, false))
             }
-            Branch::Tree(tree) | Branch::Array(Array { tree, .. }) => tree
+            Branch::Tree(tree)
+            | Branch::Array(Array { tree, .. })
+            | Branch::Map(Map { tree, .. }) => tree
                 .get_branches()
                 .into_iter()
                 .find_map(|(name, branch)| {
@@ -366,9 +1515,9 @@ impl State {
                         (name == node.text() && !branch.is_empty()).then_some(branch)
                     })
                 })
-                .map(|current| (Node::from(current), false)),
+                .map(|current| (self.first_item(current), false)),
         };
-        let Some((node, replace)) = res else {
+        let Some((node, replace)) = res.filter(|(_, replace)| *replace || !disabled) else {
             return;
         };
 
@@ -378,40 +1527,186 @@ impl State {
         self.position.push(node);
     }
 
-    fn enter_handler(&mut self, tabs: &mut crate::Branches, event: crate::Event) -> bool {
+    fn enter_handler(&mut self, tabs: &mut crate::Branches<'a>, event: crate::Event) -> Outcome {
+        let mut outcome = Outcome::None;
+        let access = Self::access_at(tabs, &self.position);
         if let Some(value) = self.current_value(tabs) {
+            if access != Access::Editable
+                && self.input.is_none()
+                && (value.inner().as_struct().is_none()
+                    || value.inner().is_array()
+                    || value.inner().is_map())
+            {
+                return Outcome::Rejected {
+                    path: self.position.clone(),
+                    reason: access.to_string(),
+                };
+            }
+            if value.is_unset() {
+                if self.input.is_none() && event == crate::Event::Enter {
+                    let old = value.clone();
+                    value.assign_default();
+                    self.history.record(Change::Replace {
+                        path: self.position.clone(),
+                        old,
+                        new: value.clone(),
+                    });
+                    outcome = Outcome::Assigned {
+                        path: self.position.clone(),
+                    };
+                }
+                return outcome;
+            }
+            if value.inner().is_map() {
+                let Some(map) = value.inner_mut().as_map_mut() else {
+                    return outcome;
+                };
+                let index = map.get_branches().len();
+                let key = map.insert_default();
+                let Some(branch) = map.get_branches().get(&key).cloned() else {
+                    return outcome;
+                };
+                let path = self.position.clone();
+                self.position.push(Node::tree(&key));
+                self.history.record(Change::Attach {
+                    path: self.position.clone(),
+                    index,
+                    branch,
+                });
+                self.key = Some(key);
+                return Outcome::Inserted { path, index };
+            }
+            let value = value.inner_mut();
+            let multiline = value.is_multiline();
             let mut to_check = false;
+            let mut restore = None;
             let check = value.check();
 
             if value.is_bool() {
                 if let Some(value) = value.as_bool_mut() {
-                    *value = !*value
+                    *value = !*value;
+                    self.history.record(Change::Toggle {
+                        path: self.position.clone(),
+                    });
+                    outcome = Outcome::Toggled {
+                        path: self.position.clone(),
+                        value: *value,
+                    };
                 };
             } else if let Some(arr) = value.as_array_mut() {
+                if let Err(reason) = arr.check_insert() {
+                    return Outcome::Rejected {
+                        path: self.position.clone(),
+                        reason,
+                    };
+                }
+                let index = arr.get_branches().len();
                 arr.insert_default(None);
-                return false;
+                if let Some(element) = arr.element(index).cloned() {
+                    self.history.record(Change::Insert {
+                        path: self.position.clone(),
+                        index,
+                        element,
+                    });
+                }
+                return Outcome::Inserted {
+                    path: self.position.clone(),
+                    index,
+                };
+            } else if value.is_enum() {
+                use crate::Event::*;
+                match (self.input.clone(), event) {
+                    (None, Enter) => self.input = value.as_choice().cloned(),
+                    (Some(_), NextItem | NextLevel) => value.cycle(true),
+                    (Some(_), PreviousItem | PreviousLevel) => value.cycle(false),
+                    (Some(saved), Enter) => {
+                        let new = value.as_choice().cloned().unwrap_or_default();
+                        if saved != new {
+                            self.history.record(Change::Edit {
+                                path: self.position.clone(),
+                                old: saved.clone(),
+                                new: new.clone(),
+                            });
+                        }
+                        outcome = Outcome::Committed {
+                            path: self.position.clone(),
+                            old: saved,
+                            new,
+                        };
+                        self.input = None;
+                    }
+                    (Some(saved), Cancel) => {
+                        value.select(&saved);
+                        self.input = None;
+                        outcome = Outcome::Cancelled {
+                            path: self.position.clone(),
+                        };
+                    }
+                    _ => (),
+                }
             } else if let Some(text) = value.as_text() {
                 use crate::Event::*;
                 let mut text = text.lock().unwrap();
                 if self.input.is_none() && event == Enter {
-                    self.input = text.lines().get(0).cloned();
+                    self.input = Some(text.lines().join("\n"));
                     to_check = true;
                 } else if let Some(saved) = &self.input {
                     match event {
                         NextLevel => text.move_cursor(tui_textarea::CursorMove::Forward),
                         PreviousLevel => text.move_cursor(tui_textarea::CursorMove::Back),
-                        Enter => {
-                            if check {
-                                self.input = None;
-                                text.move_cursor(tui_textarea::CursorMove::End);
+                        NextItem => text.move_cursor(tui_textarea::CursorMove::Down),
+                        PreviousItem => text.move_cursor(tui_textarea::CursorMove::Up),
+                        Home => text.move_cursor(tui_textarea::CursorMove::Head),
+                        End => text.move_cursor(tui_textarea::CursorMove::End),
+                        WordLeft => text.move_cursor(tui_textarea::CursorMove::WordBack),
+                        WordRight => text.move_cursor(tui_textarea::CursorMove::WordForward),
+                        NewLine if multiline => {
+                            text.insert_newline();
+                            to_check = true;
+                        }
+                        Delete => {
+                            text.delete_next_char();
+                            to_check = true;
+                        }
+                        DeleteWord => {
+                            text.delete_word();
+                            to_check = true;
+                        }
+                        InsertStr(str) => {
+                            for (index, line) in str.split('\n').enumerate() {
+                                if index > 0 && multiline {
+                                    text.insert_newline();
+                                } else if index > 0 {
+                                    text.insert_char(' ');
+                                }
+                                text.insert_str(line.trim_end_matches('\r'));
                             }
+                            to_check = true;
                         }
-                        Cancel => {
-                            text.move_cursor(tui_textarea::CursorMove::End);
-                            text.delete_line_by_head();
-                            text.insert_str(saved);
+                        Enter if check => {
+                            let new = text.lines().join("\n");
+                            if *saved != new {
+                                self.history.record(Change::Edit {
+                                    path: self.position.clone(),
+                                    old: saved.clone(),
+                                    new: new.clone(),
+                                });
+                            }
+                            outcome = Outcome::Committed {
+                                path: self.position.clone(),
+                                old: saved.clone(),
+                                new,
+                            };
+                            self.input = None;
+                            text.move_cursor(tui_textarea::CursorMove::Top);
                             text.move_cursor(tui_textarea::CursorMove::End);
+                        }
+                        Cancel => {
+                            restore = Some(saved.clone());
                             self.input = None;
+                            outcome = Outcome::Cancelled {
+                                path: self.position.clone(),
+                            };
                         }
                         Char(sym) => {
                             text.insert_char(sym);
@@ -426,6 +1721,9 @@ impl State {
                 }
             }
 
+            if let Some(saved) = restore {
+                value.set_text(&saved);
+            }
             if to_check {
                 let res = value.check();
                 if let Some(text) = value.as_text() {
@@ -438,6 +1736,941 @@ impl State {
                 };
             }
         }
-        true
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Args, Branch, Clip, Event, Node, Outcome, PathError, Rule, Tree, TreeEdit, TreeEditState,
+        Value, ValueError,
+    };
+    use linked_hash_map::LinkedHashMap;
+
+    fn fixture<'a>(tab: impl Into<Branch<'a>>) -> TreeEdit<'a> {
+        TreeEdit::new("Test").tab("Tab".to_string(), tab)
+    }
+    fn rows<'a>(rows: impl IntoIterator<Item = (&'a str, Value<'a>)>) -> Args<'a> {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        rows.iter().fold(
+            Args::default()
+                .names(rows.iter().map(|(name, _)| *name))
+                .columns(["Value"]),
+            |args, (name, value)| args.value(name, "Value", value.clone()),
+        )
+    }
+
+    #[test]
+    fn it_works() {
+        let mut tree_edit = fixture(Tree::default().branch(
+            "Branch",
+            rows([
+                ("Number", 1u8.into()),
+                ("Flag", false.into()),
+                ("List", vec![1u8].into()),
+            ]),
+        ));
+        let mut state = TreeEditState::default();
+        let cell = |name: &str| vec![Node::tree("Tab"), Node::tree("Branch"), Node::args(name, 0)];
+
+        assert_eq!(
+            state.transition(Event::NextLevel, &mut tree_edit),
+            Outcome::None
+        );
+        assert_eq!(state.position(), &cell("Number"));
+
+        state.transition(Event::Enter, &mut tree_edit);
+        assert!(state.in_input_mode());
+        state.transition(Event::Char('2'), &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Committed {
+                path: cell("Number"),
+                old: "1".into(),
+                new: "12".into(),
+            }
+        );
+
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::Home, &mut tree_edit);
+        state.transition(Event::Delete, &mut tree_edit);
+        state.transition(Event::InsertStr("4".into()), &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Committed {
+                path: cell("Number"),
+                old: "12".into(),
+                new: "42".into(),
+            }
+        );
+
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::Backspace, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Cancel, &mut tree_edit),
+            Outcome::Cancelled {
+                path: cell("Number")
+            }
+        );
+
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Toggled {
+                path: cell("Flag"),
+                value: true,
+            }
+        );
+
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Inserted {
+                path: cell("List"),
+                index: 1,
+            }
+        );
+        state.transition(Event::NextLevel, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Delete, &mut tree_edit),
+            Outcome::Removed {
+                path: cell("List"),
+                index: 0,
+            }
+        );
+
+        assert_eq!(
+            state.transition(Event::NextTab, &mut tree_edit),
+            Outcome::TabSwitched("Tab".into())
+        );
+    }
+
+    #[test]
+    fn choice() {
+        let mut tree_edit = fixture(rows([(
+            "Mode",
+            Value::choice(["Auto", "Manual", "Off"], 0),
+        )]));
+        let mut state = TreeEditState::default();
+        let choice = |tree_edit: &TreeEdit| {
+            tree_edit.get_tabs()["Tab"]
+                .as_args()
+                .and_then(|args| args.get_value("Mode", "Value"))
+                .and_then(|value| value.as_choice().cloned())
+        };
+
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::PreviousItem, &mut tree_edit);
+        assert_eq!(choice(&tree_edit), Some("Off".into()));
+        state.transition(Event::Cancel, &mut tree_edit);
+        assert_eq!(choice(&tree_edit), Some("Auto".into()));
+
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert!(matches!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Committed { old, new, .. } if old == "Auto" && new == "Manual"
+        ));
+        state.transition(Event::Undo, &mut tree_edit);
+        assert_eq!(choice(&tree_edit), Some("Auto".into()));
+    }
+
+    #[test]
+    fn multiline() {
+        let mut tree_edit = fixture(rows([("Notes", Value::multiline("a\nb"))]));
+        let mut state = TreeEditState::default();
+
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::NextItem, &mut tree_edit);
+        state.transition(Event::NewLine, &mut tree_edit);
+        state.transition(Event::Char('c'), &mut tree_edit);
+        state.transition(Event::Cancel, &mut tree_edit);
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::NewLine, &mut tree_edit);
+        assert!(matches!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Committed { old, new, .. } if old == "a\nb" && new == "a\n\nb"
+        ));
+    }
+
+    #[test]
+    fn clipboard() {
+        #[derive(Default)]
+        struct System(std::sync::Mutex<Option<String>>);
+        impl crate::SystemClipboard for std::sync::Arc<System> {
+            fn get(&self) -> Option<String> {
+                self.0.lock().unwrap().clone()
+            }
+            fn set(&self, text: String) {
+                *self.0.lock().unwrap() = Some(text);
+            }
+        }
+
+        let eth = |port: u16| rows([("Port", port.into())]);
+        let mut tree_edit = TreeEdit::new("Test")
+            .tab(
+                "A".to_string(),
+                Tree::default()
+                    .branch("eth0", eth(80))
+                    .branch("eth1", eth(81)),
+            )
+            .tab(
+                "B".to_string(),
+                Args::default()
+                    .names(["Port", "Flag", "Serial"])
+                    .columns(["Value", "Default"])
+                    .value("Port", "Value", 0u16)
+                    .value("Port", "Default", 80u16)
+                    .value("Flag", "Value", false)
+                    .value("Serial", "Value", Value::from("A-1").read_only()),
+            );
+        let system = std::sync::Arc::new(System::default());
+        let mut state = TreeEditState::default().system_clipboard(system.clone());
+        fn port(tree_edit: &TreeEdit, tab: &str, name: Option<&str>) -> Option<u16> {
+            let branch = &tree_edit.get_tabs()[tab];
+            let branch = branch.as_tree().map_or(Some(branch), |tree| {
+                tree.get_branches().get(name.unwrap_or_default())
+            })?;
+            branch.as_args()?.get_value("Port", "Value")?.get().ok()
+        }
+
+        state.transition(Event::Copy, &mut tree_edit);
+        state.transition(Event::NextItem, &mut tree_edit);
+        state.transition(Event::Paste, &mut tree_edit);
+        assert_eq!(port(&tree_edit, "A", Some("eth1")), Some(80));
+        state.transition(Event::Undo, &mut tree_edit);
+        assert_eq!(port(&tree_edit, "A", Some("eth1")), Some(81));
+
+        state.transition(Event::NextTab, &mut tree_edit);
+        assert!(matches!(
+            state.transition(Event::Paste, &mut tree_edit),
+            Outcome::Rejected { .. }
+        ));
+        *system.0.lock().unwrap() = Some("1234".into());
+        state.transition(Event::Paste, &mut tree_edit);
+        assert_eq!(port(&tree_edit, "B", None), Some(1234));
+
+        state.transition(Event::Copy, &mut tree_edit);
+        assert_eq!(*system.0.lock().unwrap(), Some("1234".into()));
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Paste, &mut tree_edit),
+            Outcome::Rejected {
+                path: vec![Node::tree("B"), Node::args("Flag", 0)],
+                reason: "Expected Bool".into(),
+            }
+        );
+
+        let port_path = vec![Node::tree("B"), Node::args("Port", 0)];
+        let default = |tree_edit: &TreeEdit| {
+            tree_edit.get_tabs()["B"]
+                .as_args()
+                .and_then(|args| args.get_value("Port", "Default"))
+                .and_then(|value| value.get::<u16>().ok())
+        };
+        state.transition(Event::PreviousItem, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Cut, &mut tree_edit),
+            Outcome::Cleared {
+                path: port_path.clone()
+            }
+        );
+        assert_eq!(port(&tree_edit, "B", None), Some(0));
+        *system.0.lock().unwrap() = Some("42".into());
+        state.transition(Event::Paste, &mut tree_edit);
+        assert_eq!(port(&tree_edit, "B", None), Some(42));
+
+        state.transition(Event::CutRow, &mut tree_edit);
+        assert_eq!(default(&tree_edit), Some(0));
+        assert_eq!(
+            state.transition(Event::Paste, &mut tree_edit),
+            Outcome::Pasted { path: port_path }
+        );
+        assert_eq!(port(&tree_edit, "B", None), Some(42));
+        assert_eq!(default(&tree_edit), Some(80));
+
+        state.select_path(&mut tree_edit, "B/Serial").unwrap();
+        assert!(matches!(
+            state.transition(Event::Cut, &mut tree_edit),
+            Outcome::Rejected { reason, .. } if reason == "Read-only"
+        ));
+        assert!(matches!(state.get_clipboard(), Some(Clip::Row(_))));
+
+        let eth = |name: &str| vec![Node::tree("A"), Node::tree(name)];
+        state.select_path(&mut tree_edit, "A/eth0").unwrap();
+        assert_eq!(
+            state.transition(Event::Cut, &mut tree_edit),
+            Outcome::Deleted { path: eth("eth0") }
+        );
+        assert_eq!(state.position(), &eth("eth1"));
+        assert_eq!(port(&tree_edit, "A", Some("eth0")), None);
+        state.transition(Event::Paste, &mut tree_edit);
+        assert_eq!(port(&tree_edit, "A", Some("eth1")), Some(80));
+        state.transition(Event::Undo, &mut tree_edit);
+        state.transition(Event::Undo, &mut tree_edit);
+        assert_eq!(port(&tree_edit, "A", Some("eth0")), Some(80));
+        assert_eq!(port(&tree_edit, "A", Some("eth1")), Some(81));
+
+        let mut tree_edit = fixture(rows([
+            ("Limit", Value::from(5u8).rule(Rule::Max(10.0))),
+            ("Large", 50u8.into()),
+        ]));
+        let mut state = TreeEditState::default();
+        state.transition(Event::NextItem, &mut tree_edit);
+        state.transition(Event::CopyRow, &mut tree_edit);
+        state.transition(Event::PreviousItem, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Paste, &mut tree_edit),
+            Outcome::Rejected {
+                path: vec![Node::tree("Tab"), Node::args("Limit", 0)],
+                reason: "Must be <= 10".into(),
+            }
+        );
+        assert_eq!(
+            tree_edit.get_tabs()["Tab"]
+                .as_args()
+                .and_then(|args| args.get_value("Limit", "Value"))
+                .map(|value| value.get()),
+            Some(Ok(5u8))
+        );
+    }
+
+    #[test]
+    fn search() {
+        let eth =
+            |address: &'static str| rows([("Address", address.into()), ("Port", 80u16.into())]);
+        let mut tree_edit = TreeEdit::new("Test")
+            .tab(
+                "Network".to_string(),
+                Tree::default()
+                    .branch("eth0", eth("10.0.0.1"))
+                    .branch("eth1", eth("10.0.0.2")),
+            )
+            .tab(
+                "Gateway".to_string(),
+                rows([("Address", "10.0.0.254".into())]),
+            );
+        let mut state = TreeEditState::default();
+        let cell = |tab: &str, branch: Option<&str>| {
+            [Node::tree(tab)]
+                .into_iter()
+                .chain(branch.map(Node::tree))
+                .chain([Node::args("Address", 0)])
+                .collect::<Vec<_>>()
+        };
+
+        state.transition(Event::Search, &mut tree_edit);
+        assert!(state.in_search_mode());
+        for sym in "0.0.2".chars() {
+            state.transition(Event::Char(sym), &mut tree_edit);
+        }
+        assert_eq!(state.position(), &cell("Network", Some("eth1")));
+        state.transition(Event::Backspace, &mut tree_edit);
+        assert_eq!(state.position(), &cell("Network", Some("eth0")));
+        assert_eq!(state.search_matches(&tree_edit).len(), 3);
+        state.transition(Event::Enter, &mut tree_edit);
+        assert!(!state.in_search_mode());
+
+        state.transition(Event::NextMatch, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::NextMatch, &mut tree_edit),
+            Outcome::Found {
+                path: cell("Gateway", None),
+                index: 2,
+                count: 3,
+            }
+        );
+        state.transition(Event::NextMatch, &mut tree_edit);
+        assert_eq!(state.position(), &cell("Network", Some("eth0")));
+        state.transition(Event::PreviousMatch, &mut tree_edit);
+        assert_eq!(state.position(), &cell("Gateway", None));
+
+        state.transition(Event::Search, &mut tree_edit);
+        for sym in "eth1".chars() {
+            state.transition(Event::Char(sym), &mut tree_edit);
+        }
+        assert_eq!(
+            state.position(),
+            &vec![Node::tree("Network"), Node::tree("eth1")]
+        );
+        state.transition(Event::Cancel, &mut tree_edit);
+        assert_eq!(state.position(), &cell("Gateway", None));
+        assert_eq!(state.search_query(), None);
+    }
+
+    #[test]
+    fn select_path() {
+        let mut tree_edit = TreeEdit::new("Test").tab(
+            "Network".to_string(),
+            Tree::default().branch(
+                "eth0",
+                Args::default()
+                    .names(["Address", "Port"])
+                    .columns(["Value", "Default"])
+                    .value("Address", "Value", "127.0.0.1")
+                    .value("Port", "Value", 8080u16),
+            ),
+        );
+        let mut state = TreeEditState::default();
+
+        assert_eq!(
+            state.select_path(&mut tree_edit, "Network/eth0/(Port, Default)"),
+            Ok(())
+        );
+        assert_eq!(
+            state.position(),
+            &vec![
+                Node::tree("Network"),
+                Node::tree("eth0"),
+                Node::args("Port", 1)
+            ]
+        );
+        state
+            .select_path(&mut tree_edit, ["Network", "eth0", "Address"])
+            .unwrap();
+        assert_eq!(state.position().last(), Some(&Node::args("Address", 0)));
+        state.select_path(&mut tree_edit, "Network").unwrap();
+        assert_eq!(state.position().last(), Some(&Node::tree("eth0")));
+
+        assert_eq!(
+            state.select_path(&mut tree_edit, "Network/eth1"),
+            Err(PathError::Branch("eth1".into()))
+        );
+        assert_eq!(
+            state.select_path(&mut tree_edit, "Network/eth0/(Port, Min)"),
+            Err(PathError::Column("Min".into()))
+        );
+        assert_eq!(
+            state.select_path(&mut tree_edit, "Network/eth0/Port/Number"),
+            Err(PathError::Leaf("Port".into()))
+        );
+        assert_eq!(state.position().last(), Some(&Node::tree("eth0")));
+    }
+
+    #[test]
+    fn access() {
+        let mut tree_edit = fixture(rows([
+            ("Serial", Value::from("A-1").read_only()),
+            ("Debug", Value::from(false).disabled()),
+            ("Name", "Sensor".into()),
+            ("Ports", Value::from(vec![1u8]).read_only()),
+        ]));
+        let mut state = TreeEditState::default();
+        let cell = |name: &str| vec![Node::tree("Tab"), Node::args(name, 0)];
+
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Rejected {
+                path: cell("Serial"),
+                reason: "Read-only".into(),
+            }
+        );
+        assert!(!state.in_input_mode());
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert!(matches!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Rejected { reason, .. } if reason == "Disabled"
+        ));
+
+        state.transition(Event::NextItem, &mut tree_edit);
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert!(matches!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Rejected { .. }
+        ));
+        assert_eq!(state.position().last(), Some(&Node::tree("0")));
+        assert!(matches!(
+            state.transition(Event::Delete, &mut tree_edit),
+            Outcome::Rejected { .. }
+        ));
+        assert_eq!(tree_edit.get_at("Tab/Ports").unwrap().get(), Ok(vec![1u8]));
+
+        let mut state = TreeEditState::default().skip_disabled(true);
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert_eq!(state.position(), &cell("Name"));
+        state.transition(Event::PreviousItem, &mut tree_edit);
+        assert_eq!(state.position(), &cell("Serial"));
+
+        let mut tree_edit = fixture(
+            Tree::default()
+                .branch("Hidden", rows([("Port", 80u16.into())]).disabled())
+                .branch("Shown", rows([("Port", 80u16.into())])),
+        )
+        .tab(
+            "Other".to_string(),
+            rows([
+                ("Debug", Value::from(false).disabled()),
+                ("Name", "Sensor".into()),
+            ]),
+        );
+        let mut state = TreeEditState::default();
+        state.transition(Event::NextLevel, &mut tree_edit);
+        assert_eq!(
+            state.position(),
+            &vec![Node::tree("Tab"), Node::tree("Hidden")]
+        );
+
+        let mut state = TreeEditState::default().skip_disabled(true);
+        state.transition(Event::PreviousLevel, &mut tree_edit);
+        assert_eq!(
+            state.position(),
+            &vec![Node::tree("Tab"), Node::tree("Shown")]
+        );
+        state.transition(Event::NextTab, &mut tree_edit);
+        assert_eq!(
+            state.position(),
+            &vec![Node::tree("Other"), Node::args("Name", 0)]
+        );
+        state.transition(Event::PreviousTab, &mut tree_edit);
+        assert_eq!(
+            state.position(),
+            &vec![Node::tree("Tab"), Node::tree("Shown")]
+        );
+
+        let mut tree_edit = fixture(
+            Args::default()
+                .names(["Source", "Target"])
+                .columns(["Value", "Serial"])
+                .value("Source", "Value", 1u8)
+                .value("Source", "Serial", 5u8)
+                .value("Target", "Value", 2u8)
+                .value("Target", "Serial", Value::from(9u8).read_only()),
+        );
+        let mut state = TreeEditState::default();
+        state.transition(Event::CopyRow, &mut tree_edit);
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Paste, &mut tree_edit),
+            Outcome::Rejected {
+                path: vec![Node::tree("Tab"), Node::args("Target", 0)],
+                reason: "Read-only".into(),
+            }
+        );
+        assert_eq!(
+            tree_edit.get_at("Tab/(Target, Value)").unwrap().get(),
+            Ok(2u8)
+        );
+        assert_eq!(
+            tree_edit.get_at("Tab/(Target, Serial)").unwrap().get(),
+            Ok(9u8)
+        );
+    }
+
+    #[test]
+    fn array_elements() {
+        let mut tree_edit = fixture(rows([("List", vec![1u8, 2, 3].into())]));
+        let mut state = TreeEditState::default();
+        let list = |tree_edit: &TreeEdit| tree_edit.get_at("Tab/List").unwrap().get::<Vec<u8>>();
+        let list_path = vec![Node::tree("Tab"), Node::args("List", 0)];
+
+        state.transition(Event::NextLevel, &mut tree_edit);
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::MoveUp, &mut tree_edit),
+            Outcome::Moved {
+                path: list_path.clone(),
+                from: 1,
+                to: 0,
+            }
+        );
+        assert_eq!(list(&tree_edit), Ok(vec![2, 1, 3]));
+        assert_eq!(state.position().last(), Some(&Node::tree("0")));
+        assert_eq!(
+            state.transition(Event::MoveUp, &mut tree_edit),
+            Outcome::None
+        );
+
+        state.transition(Event::Duplicate, &mut tree_edit);
+        assert_eq!(list(&tree_edit), Ok(vec![2, 2, 1, 3]));
+        assert_eq!(state.position().last(), Some(&Node::tree("1")));
+        assert_eq!(
+            state.transition(Event::InsertBefore, &mut tree_edit),
+            Outcome::Inserted {
+                path: list_path,
+                index: 1,
+            }
+        );
+        state.transition(Event::InsertAfter, &mut tree_edit);
+        assert_eq!(list(&tree_edit), Ok(vec![2, 0, 0, 2, 1, 3]));
+        assert_eq!(state.position().last(), Some(&Node::tree("2")));
+
+        state.transition(Event::NextItem, &mut tree_edit);
+        state.transition(Event::MoveDown, &mut tree_edit);
+        assert_eq!(list(&tree_edit), Ok(vec![2, 0, 0, 1, 2, 3]));
+        state.transition(Event::Undo, &mut tree_edit);
+        assert_eq!(list(&tree_edit), Ok(vec![2, 0, 0, 2, 1, 3]));
+        assert_eq!(state.position().last(), Some(&Node::tree("3")));
+    }
+
+    #[test]
+    fn array_limits() {
+        let mut tree_edit = fixture(rows([
+            ("Pair", [1u8, 2].into()),
+            ("List", Value::from(vec![1u8]).max_len(2)),
+        ]));
+        let mut state = TreeEditState::default();
+        let rejected = |path: &[&str], reason: &str| Outcome::Rejected {
+            path: vec![Node::tree("Tab"), Node::args("Pair", 0)]
+                .into_iter()
+                .chain(path.iter().map(|name| Node::tree(*name)))
+                .collect(),
+            reason: reason.into(),
+        };
+
+        state.transition(Event::NextLevel, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Duplicate, &mut tree_edit),
+            rejected(&["0"], "Fixed size")
+        );
+        assert_eq!(
+            state.transition(Event::Delete, &mut tree_edit),
+            rejected(&["0"], "Fixed size")
+        );
+        assert_eq!(
+            tree_edit.remove_at("Tab/Pair/1").err(),
+            Some(PathError::Fixed("1".into()))
+        );
+
+        tree_edit.set_at("Tab/List", vec![1u8, 2]).unwrap();
+        assert!(matches!(
+            tree_edit.set_at("Tab/List", vec![1u8, 2, 3]),
+            Err(PathError::Value(ValueError::Invalid { .. }))
+        ));
+        let mut list = tree_edit.get_at("Tab/List").unwrap().clone();
+        list.assign(&Value::from(vec![1u8, 2, 3]));
+        assert_eq!(list.validate(), Err("At most 2 elements".into()));
+        assert_eq!(tree_edit.get_at("Tab/Pair").unwrap().validate(), Ok(()));
+    }
+
+    #[test]
+    fn map_entries() {
+        let env = [("PATH", "/bin"), ("HOME", "/root")]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<LinkedHashMap<_, _>>();
+        let mut tree_edit = fixture(rows([("Env", env.into())]));
+        let mut state = TreeEditState::default();
+        let keys = |tree_edit: &TreeEdit| {
+            tree_edit
+                .get_at("Tab/Env")
+                .unwrap()
+                .get::<LinkedHashMap<String, String>>()
+                .map(|env| env.keys().cloned().collect::<Vec<_>>())
+        };
+        let env_path = vec![Node::tree("Tab"), Node::args("Env", 0)];
+        let key_path = |key: &str| {
+            let mut path = env_path.clone();
+            path.push(Node::tree(key));
+            path
+        };
+
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Inserted {
+                path: env_path.clone(),
+                index: 2,
+            }
+        );
+        assert!(state.in_rename_mode());
+        assert_eq!(state.position(), &key_path("key"));
+        (0..3).for_each(|_| {
+            state.transition(Event::Backspace, &mut tree_edit);
+        });
+        "LANG".chars().for_each(|sym| {
+            state.transition(Event::Char(sym), &mut tree_edit);
+        });
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Renamed {
+                path: key_path("LANG"),
+                old: "key".into(),
+                new: "LANG".into(),
+            }
+        );
+        assert_eq!(
+            keys(&tree_edit),
+            Ok(vec!["PATH".into(), "HOME".into(), "LANG".into()])
+        );
+
+        state.transition(Event::Enter, &mut tree_edit);
+        assert!(!state.in_rename_mode());
+        let mut value_path = key_path("LANG");
+        value_path.push(Node::args("", 0));
+        assert_eq!(state.position(), &value_path);
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::InsertStr("C".into()), &mut tree_edit);
+        state.transition(Event::Enter, &mut tree_edit);
+        assert_eq!(
+            tree_edit
+                .get_at("Tab/Env")
+                .unwrap()
+                .get::<LinkedHashMap<String, String>>()
+                .map(|env| env["LANG"].clone()),
+            Ok("C".to_string())
+        );
+        state.transition(Event::PreviousLevel, &mut tree_edit);
+
+        state.transition(Event::Rename, &mut tree_edit);
+        state.transition(Event::DeleteWord, &mut tree_edit);
+        state.transition(Event::InsertStr("PATH".into()), &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Rejected {
+                path: key_path("LANG"),
+                reason: "Key \"PATH\" already exists".into(),
+            }
+        );
+        state.transition(Event::Cancel, &mut tree_edit);
+        assert!(!state.in_rename_mode());
+
+        state.transition(Event::PreviousItem, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Delete, &mut tree_edit),
+            Outcome::Removed {
+                path: env_path.clone(),
+                index: 1,
+            }
+        );
+        assert_eq!(state.position(), &key_path("LANG"));
+        assert_eq!(keys(&tree_edit), Ok(vec!["PATH".into(), "LANG".into()]));
+        assert_eq!(
+            state.transition(Event::Undo, &mut tree_edit),
+            Outcome::Created {
+                path: key_path("HOME")
+            }
+        );
+        assert_eq!(
+            keys(&tree_edit),
+            Ok(vec!["PATH".into(), "HOME".into(), "LANG".into()])
+        );
+    }
+
+    #[test]
+    fn rename() {
+        let mut tree_edit = TreeEdit::new("Test")
+            .tab(
+                "Network".to_string(),
+                Tree::default()
+                    .renamable()
+                    .branch(
+                        "eth0",
+                        Args::default()
+                            .renamable()
+                            .names(["Port"])
+                            .columns(["Value"])
+                            .value("Port", "Value", 80u16),
+                    )
+                    .branch("eth1", Args::default()),
+            )
+            .tab(
+                "Fixed".to_string(),
+                Tree::default().branch("eth0", Args::default()),
+            );
+        let mut state = TreeEditState::default();
+        let rejected = |path: Vec<Node>, reason: &str| Outcome::Rejected {
+            path,
+            reason: reason.into(),
+        };
+        let eth = |name: &str| vec![Node::tree("Network"), Node::tree(name)];
+
+        state.transition(Event::Rename, &mut tree_edit);
+        assert!(state.in_rename_mode());
+        state.transition(Event::DeleteWord, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            rejected(eth("eth0"), "Empty name")
+        );
+        state.transition(Event::InsertStr("eth1".into()), &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            rejected(eth("eth0"), "Name \"eth1\" already exists")
+        );
+        state.transition(Event::Backspace, &mut tree_edit);
+        state.transition(Event::Char('2'), &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Renamed {
+                path: eth("eth2"),
+                old: "eth0".into(),
+                new: "eth2".into(),
+            }
+        );
+        let Some((_, Branch::Tree(tree))) = tree_edit.get_tabs().front() else {
+            panic!("tab must be a tree");
+        };
+        assert_eq!(
+            tree.get_branches().keys().collect::<Vec<_>>(),
+            vec!["eth2", "eth1"]
+        );
+
+        state.transition(Event::NextLevel, &mut tree_edit);
+        state.transition(Event::Rename, &mut tree_edit);
+        state.transition(Event::DeleteWord, &mut tree_edit);
+        state.transition(Event::InsertStr("Listen".into()), &mut tree_edit);
+        state.transition(Event::Enter, &mut tree_edit);
+        assert_eq!(
+            tree_edit.get_at("Network/eth2/Listen").unwrap().get(),
+            Ok(80u16)
+        );
+
+        state.transition(Event::Undo, &mut tree_edit);
+        assert!(tree_edit.get_at("Network/eth2/Port").is_ok());
+        state.transition(Event::Undo, &mut tree_edit);
+        assert_eq!(state.position(), &eth("eth0"));
+        assert!(tree_edit.get_at("Network/eth0/Port").is_ok());
+
+        state.transition(Event::NextTab, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Rename, &mut tree_edit),
+            rejected(
+                vec![Node::tree("Fixed"), Node::tree("eth0")],
+                "Not renamable"
+            )
+        );
+        assert!(!state.in_rename_mode());
+    }
+
+    #[test]
+    fn runtime_branches() {
+        let mut tree_edit = TreeEdit::new("Test")
+            .tab(
+                "Network".to_string(),
+                Tree::default().branch("eth0", Args::default()),
+            )
+            .template("Interface", || rows([("Port", 0u16.into())]).into())
+            .template("Group", || Tree::default().into());
+        let mut state = TreeEditState::default();
+        let names = |branches: &crate::Branches| branches.keys().cloned().collect::<Vec<_>>();
+        let branches = |tree_edit: &TreeEdit| match tree_edit.get_tabs().front() {
+            Some((_, Branch::Tree(tree))) => names(tree.get_branches()),
+            _ => Vec::new(),
+        };
+        let eth = |name: &str| vec![Node::tree("Network"), Node::tree(name)];
+
+        assert_eq!(
+            state.transition(Event::NewBranch, &mut tree_edit),
+            Outcome::Creating {
+                path: eth("Interface"),
+                template: Some("Interface".into()),
+            }
+        );
+        assert!(state.in_rename_mode());
+        assert_eq!(state.position(), &eth("Interface"));
+        assert_eq!(
+            state.transition(Event::NextItem, &mut tree_edit),
+            Outcome::Creating {
+                path: eth("Interface"),
+                template: Some("Group".into()),
+            }
+        );
+        assert_eq!(state.get_template(&tree_edit), Some(&"Group".to_string()));
+        assert!(tree_edit.get_at("Network/Interface/Port").is_err());
+        state.transition(Event::PreviousItem, &mut tree_edit);
+        state.transition(Event::DeleteWord, &mut tree_edit);
+        state.transition(Event::InsertStr("eth1".into()), &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Created { path: eth("eth1") }
+        );
+        assert_eq!(branches(&tree_edit), vec!["eth0", "eth1"]);
+        assert_eq!(
+            tree_edit.get_at("Network/eth1/Port").unwrap().get(),
+            Ok(0u16)
+        );
+
+        assert_eq!(
+            state.transition(Event::CloneBranch, &mut tree_edit),
+            Outcome::Creating {
+                path: eth("eth1 2"),
+                template: None,
+            }
+        );
+        assert_eq!(state.position(), &eth("eth1 2"));
+        state.transition(Event::Cancel, &mut tree_edit);
+        assert_eq!(state.position(), &eth("eth1"));
+        assert_eq!(branches(&tree_edit), vec!["eth0", "eth1"]);
+        state.transition(Event::PreviousItem, &mut tree_edit);
+        state.transition(Event::CloneBranch, &mut tree_edit);
+        state.transition(Event::Enter, &mut tree_edit);
+        assert_eq!(branches(&tree_edit), vec!["eth0", "eth0 2", "eth1"]);
+
+        assert_eq!(
+            state.transition(Event::RemoveBranch, &mut tree_edit),
+            Outcome::Confirm {
+                path: eth("eth0 2")
+            }
+        );
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert_eq!(branches(&tree_edit), vec!["eth0", "eth0 2", "eth1"]);
+        state.transition(Event::RemoveBranch, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Deleted {
+                path: eth("eth0 2")
+            }
+        );
+        assert_eq!(state.position(), &eth("eth1"));
+        state.transition(Event::Undo, &mut tree_edit);
+        assert_eq!(branches(&tree_edit), vec!["eth0", "eth0 2", "eth1"]);
+
+        state.transition(Event::NewTab, &mut tree_edit);
+        assert_eq!(
+            state.transition(Event::Enter, &mut tree_edit),
+            Outcome::Created {
+                path: vec![Node::tree("Interface")]
+            }
+        );
+        assert_eq!(names(tree_edit.get_tabs()), vec!["Network", "Interface"]);
+        assert_eq!(
+            state.position(),
+            &vec![Node::tree("Interface"), Node::args("Port", 0)]
+        );
+        state.transition(Event::RemoveTab, &mut tree_edit);
+        state.transition(Event::Enter, &mut tree_edit);
+        assert_eq!(names(tree_edit.get_tabs()), vec!["Network"]);
+        assert_eq!(state.position().first(), Some(&Node::tree("Network")));
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut tree_edit = fixture(rows([
+            ("Number", 1u8.into()),
+            ("List", vec![1u8, 2].into()),
+        ]));
+        let mut state = TreeEditState::default();
+        fn value<'a>(tree_edit: &TreeEdit<'a>, name: &str) -> Value<'a> {
+            tree_edit.get_tabs()["Tab"]
+                .as_args()
+                .and_then(|args| args.get_value(name, "Value"))
+                .cloned()
+                .unwrap()
+        }
+
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::Char('5'), &mut tree_edit);
+        state.transition(Event::Enter, &mut tree_edit);
+        state.transition(Event::NextItem, &mut tree_edit);
+        state.transition(Event::NextLevel, &mut tree_edit);
+        state.transition(Event::Delete, &mut tree_edit);
+        assert_eq!(value(&tree_edit, "List").get::<Vec<u8>>(), Ok(vec![2]));
+
+        state.transition(Event::Undo, &mut tree_edit);
+        assert_eq!(value(&tree_edit, "List").get::<Vec<u8>>(), Ok(vec![1, 2]));
+        assert_eq!(state.position().last(), Some(&Node::tree("0")));
+        assert!(matches!(
+            state.transition(Event::Undo, &mut tree_edit),
+            Outcome::Committed { old, new, .. } if old == "15" && new == "1"
+        ));
+        assert_eq!(value(&tree_edit, "Number").get::<u8>(), Ok(1));
+        assert_eq!(
+            state.position().last().map(Node::text),
+            Some(&"Number".into())
+        );
+
+        state.transition(Event::Redo, &mut tree_edit);
+        state.transition(Event::Redo, &mut tree_edit);
+        assert_eq!(value(&tree_edit, "Number").get::<u8>(), Ok(15));
+        assert_eq!(value(&tree_edit, "List").get::<Vec<u8>>(), Ok(vec![2]));
     }
 }
@@ -1,13 +1,18 @@
+use std::sync::atomic::Ordering;
+
 use tui::layout::{Constraint, Direction, Layout};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
 use tui::widgets::{Block, Borders, List, ListItem, ListState, StatefulWidget};
 
-use crate::state::State;
-use crate::widget::DrawerRef;
-use crate::Branch;
+use crate::widget::{Context, DrawerRef};
+use crate::{Access, Branch};
 
 #[derive(Debug, Default, Clone)]
 pub struct Tree<'a> {
     pub(crate) branches: crate::Branches<'a>,
+    access: Access,
+    renamable: bool,
 }
 impl<'a> Tree<'a> {
     pub fn branch(mut self, branch_name: impl ToString, branch: impl Into<Branch<'a>>) -> Self {
@@ -15,53 +20,153 @@ impl<'a> Tree<'a> {
         self
     }
 
+    pub fn read_only(mut self) -> Self {
+        self.access = self.access.max(Access::ReadOnly);
+        self
+    }
+    pub fn disabled(mut self) -> Self {
+        self.access = Access::Disabled;
+        self
+    }
+    pub fn get_access(&self) -> Access {
+        self.access
+    }
+
+    pub fn renamable(mut self) -> Self {
+        self.renamable = true;
+        self
+    }
+    pub fn is_renamable(&self) -> bool {
+        self.renamable
+    }
+
     pub fn get_branches(&self) -> &crate::Branches<'a> {
         &self.branches
     }
     pub fn get_branches_mut(&mut self) -> &mut crate::Branches<'a> {
         &mut self.branches
     }
+
+    pub(crate) fn rekey(&mut self, old: &str, new: &str) -> bool {
+        crate::branch::rekey(&mut self.branches, old, new)
+    }
+
+    pub(crate) fn same_shape(&self, other: &Tree) -> bool {
+        self.branches.len() == other.branches.len()
+            && self
+                .branches
+                .iter()
+                .zip(other.branches.iter())
+                .all(|((l_name, l), (r_name, r))| l_name == r_name && l.same_shape(r))
+    }
+
+    pub(crate) fn same_content(&self, other: &Tree) -> bool {
+        self.branches.len() == other.branches.len()
+            && self
+                .branches
+                .iter()
+                .zip(other.branches.iter())
+                .all(|((l_name, l), (r_name, r))| l_name == r_name && l.same_content(r))
+    }
+
+    fn width(&self, state: &(&Context, &mut usize)) -> u16 {
+        self.branches
+            .iter()
+            .map(|(name, _)| name.len() as u16)
+            .chain(self.key(state).map(|key| key.len() as u16 + 1))
+            .fold(0, |max, width| max.max(width + 5))
+    }
+    fn key<'s>(&self, state: &(&'s Context, &mut usize)) -> Option<&'s String> {
+        state
+            .0
+            .key
+            .filter(|_| *state.1 + 1 == state.0.position.len())
+    }
+    fn current(&self, state: &(&Context, &mut usize)) -> Option<usize> {
+        state.0.node(*state.1).and_then(|current| {
+            current
+                .as_tree()
+                .and_then(|current| self.branches.keys().position(|name| name == current))
+        })
+    }
 }
 impl DrawerRef for Tree<'_> {
     fn render(
         &self,
         area: tui::layout::Rect,
         buf: &mut tui::buffer::Buffer,
-        state: (&State, &mut usize),
+        state: (&Context, &mut usize),
     ) -> u16 {
         if self.branches.is_empty() || *state.1 > state.0.position.len() {
             return 0;
         }
-        let items = {
-            self.branches
-                .iter()
-                .map(|(name, _)| name)
-                .collect::<Vec<&String>>()
+        let current = self.current(&state);
+        let access = state.0.access.get().max(self.access);
+        state.0.access.set(access);
+        if *state.1 < state.0.first_level {
+            return current
+                .and_then(|index| self.branches.iter().nth(index))
+                .map_or(0, |(_, branch)| {
+                    *state.1 += 1;
+                    branch.render(area, buf, state)
+                });
+        }
+
+        let constrains = vec![Constraint::Length(self.width(&state)), Constraint::Min(3)];
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(constrains)
+            .split(area);
+
+        let skip = match (current, state.0.node(*state.1)) {
+            (Some(index), Some(node)) => {
+                let offset = node.offset();
+                let height = chunks[0].height.max(1) as usize;
+                if index >= offset.load(Ordering::Relaxed) + height {
+                    offset.store(index + 1 - height, Ordering::Relaxed);
+                } else if index < offset.load(Ordering::Relaxed) {
+                    offset.store(index, Ordering::Relaxed);
+                }
+                offset.load(Ordering::Relaxed)
+            }
+            _ => 0,
         };
-        let width = items
-            .iter()
-            .map(|span| span.len() as u16)
-            .fold(0, |max, width| max.max(width + 5));
-        let constrains = vec![Constraint::Length(width), Constraint::Min(3)];
-        let mut list_state = ListState::default();
 
-        let current =
-            state.0.node(*state.1).and_then(|current| {
-                current.as_tree().and_then(|current| {
-                    items.iter().enumerate().find_map(|(number, &text)| {
-                        if text == current {
-                            Some(number)
+        let key = self.key(&state);
+        let list = List::new(
+            self.branches
+                .iter()
+                .enumerate()
+                .skip(skip)
+                .map(|(index, (text, branch))| {
+                    if let Some(key) = key.filter(|_| current == Some(index)) {
+                        let color = if key.is_empty()
+                            || key != text && self.branches.contains_key(key.as_str())
+                        {
+                            Color::Red
                         } else {
-                            None
-                        }
+                            Color::Green
+                        };
+                        return ListItem::new(Spans::from(vec![
+                            Span::styled(key.clone(), Style::default().fg(color)),
+                            Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED)),
+                        ]));
+                    }
+                    let style = state.0.access_style(access.max(branch.get_access()));
+                    let item = if state
+                        .0
+                        .is_dirty(*state.1, |node| node.as_tree() == Some(text))
+                    {
+                        format!("{text}*")
+                    } else {
+                        text.clone()
+                    };
+                    ListItem::new(item).style(if state.0.is_match(text) {
+                        style.patch(state.0.match_style)
+                    } else {
+                        style
                     })
                 })
-            });
-
-        let list = List::new(
-            items
-                .into_iter()
-                .map(|text| ListItem::new(text.clone()))
                 .collect::<Vec<ListItem>>(),
         )
         .block(Block::default().borders(Borders::RIGHT))
@@ -73,18 +178,15 @@ impl DrawerRef for Tree<'_> {
         .repeat_highlight_symbol(true)
         .style(state.0.style)
         .highlight_style(
-            if *state.1 < state.0.position.len() && state.0.input.is_none() {
+            if *state.1 < state.0.position.len() && state.0.input.is_none() && key.is_none() {
                 state.0.highlight_style
             } else {
                 state.0.style
             },
         );
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(constrains)
-            .split(area);
 
-        list_state.select(Some(current.unwrap_or(usize::MAX)));
+        let mut list_state = ListState::default();
+        list_state.select(Some(current.map_or(usize::MAX, |index| index - skip)));
         list.render(chunks[0], buf, &mut list_state);
         if let Some(index) = current {
             *state.1 += 1;
@@ -94,4 +196,18 @@ impl DrawerRef for Tree<'_> {
         };
         chunks[0].width
     }
+
+    fn measure(&self, state: (&Context, &mut usize), widths: &mut Vec<u16>) {
+        if self.branches.is_empty() || *state.1 > state.0.position.len() {
+            return;
+        }
+        widths.push(self.width(&state));
+        if let Some((_, branch)) = self
+            .current(&state)
+            .and_then(|index| self.branches.iter().nth(index))
+        {
+            *state.1 += 1;
+            branch.measure(state, widths)
+        }
+    }
 }
@@ -1,17 +1,82 @@
-use tui::buffer::Buffer;
-use tui::layout::{Constraint, Direction, Layout, Rect};
-use tui::text::Spans;
-use tui::widgets::{Block, Borders, Tabs, Widget};
+use tui::layout::{Constraint, Direction, Layout};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, StatefulWidget, Tabs, Widget};
 
-use crate::state::State;
+use crate::search;
+use crate::state::{Node, State};
+use crate::Access;
 
 use super::TreeEdit;
 
-pub struct Drawer<'a, 'b>(&'b TreeEdit<'a>);
+pub struct Drawer<'a, 'b> {
+    tree_edit: &'b TreeEdit<'a>,
+    style: Style,
+    highlight_style: Style,
+    match_style: Style,
+}
 
 impl Drawer<'_, '_> {
     pub fn new<'a, 'b>(tree_edit: &'b TreeEdit<'a>) -> Drawer<'a, 'b> {
-        Drawer(tree_edit)
+        Drawer {
+            tree_edit,
+            style: Default::default(),
+            highlight_style: Default::default(),
+            match_style: Style::default().add_modifier(Modifier::UNDERLINED),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+    pub fn match_style(mut self, style: Style) -> Self {
+        self.match_style = style;
+        self
+    }
+}
+
+pub struct Context<'s> {
+    pub position: &'s Vec<Node>,
+    pub input: &'s Option<String>,
+    pub key: Option<&'s String>,
+    pub search: Option<&'s String>,
+    pub dirty: &'s [Vec<Node>],
+    pub first_level: usize,
+    pub style: Style,
+    pub highlight_style: Style,
+    pub match_style: Style,
+    pub access: std::cell::Cell<Access>,
+}
+impl Context<'_> {
+    pub fn node(&self, level: usize) -> Option<&Node> {
+        self.position.get(level)
+    }
+    pub fn access_style(&self, access: Access) -> Style {
+        match access {
+            Access::Editable => Style::default(),
+            Access::ReadOnly => Style::default().add_modifier(Modifier::DIM | Modifier::ITALIC),
+            Access::Disabled => {
+                Style::default().add_modifier(Modifier::DIM | Modifier::CROSSED_OUT)
+            }
+        }
+    }
+    pub fn is_dirty(&self, level: usize, is_node: impl Fn(&Node) -> bool) -> bool {
+        self.dirty.iter().any(|path| {
+            path.get(level).map_or(false, &is_node)
+                && self
+                    .position
+                    .get(..level)
+                    .map_or(false, |prefix| path[..level] == *prefix)
+        })
+    }
+    pub fn is_match(&self, text: &str) -> bool {
+        self.search
+            .map_or(false, |query| search::is_match(query, text))
     }
 }
 
@@ -20,33 +85,82 @@ pub trait DrawerRef {
         &self,
         area: tui::layout::Rect,
         buf: &mut tui::buffer::Buffer,
-        state: (&State, &mut usize),
+        state: (&Context, &mut usize),
     ) -> u16;
+
+    fn measure(&self, state: (&Context, &mut usize), widths: &mut Vec<u16>);
 }
 
-impl Widget for Drawer<'_, '_> {
-    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+impl<'a> StatefulWidget for Drawer<'a, '_> {
+    type State = State<'a>;
+
+    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer, state: &mut State<'a>) {
         if area.area() == 0 {
             return;
         }
+        state.sync(&self.tree_edit.tabs);
 
+        let dirty = self.tree_edit.dirty_paths();
         let tab_titles = {
-            self.0
+            let context = state.context(self.style, self.highlight_style, self.match_style, &dirty);
+            self.tree_edit
                 .tabs
                 .iter()
-                .map(|(tab_name, _)| tab_name.clone().into())
+                .map(|(tab_name, _)| {
+                    if let Some(key) = context.key.filter(|_| {
+                        context.position.len() == 1
+                            && context
+                                .node(0)
+                                .map_or(false, |node| node.text() == tab_name)
+                    }) {
+                        let color = if key.is_empty()
+                            || key != tab_name && self.tree_edit.tabs.contains_key(key.as_str())
+                        {
+                            Color::Red
+                        } else {
+                            Color::Green
+                        };
+                        return Spans::from(vec![
+                            Span::styled(key.clone(), Style::default().fg(color)),
+                            Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED)),
+                        ]);
+                    }
+                    let title = if context.is_dirty(0, |node| node.as_tree() == Some(tab_name)) {
+                        format!("{tab_name}*")
+                    } else {
+                        tab_name.clone()
+                    };
+                    if context.is_match(tab_name) {
+                        Span::styled(title, self.match_style).into()
+                    } else {
+                        title.into()
+                    }
+                })
                 .collect::<Vec<Spans>>()
         };
-        let path_segments = {
-            self.0
+        let path_segments = if let Some(path) = state.get_confirmation() {
+            let name = path.last().map(Node::text).cloned().unwrap_or_default();
+            vec![format!("Remove \"{name}\"? Enter to confirm").into()]
+        } else if state.in_create_mode() {
+            let key = state.get_key().cloned().unwrap_or_default();
+            vec![match state.get_template(self.tree_edit) {
+                Some(template) => format!("Create \"{key}\" from {template}? Enter to confirm"),
+                None => format!("Create \"{key}\"? Enter to confirm"),
+            }
+            .into()]
+        } else if state.in_search_mode() {
+            let query = state.search_query().cloned().unwrap_or_default();
+            vec![format!("/{query}").into()]
+        } else {
+            state
                 .position()
                 .iter()
                 .filter_map(|node| match node {
-                    crate::Node::Tree(name) => Some(name.clone()),
+                    crate::Node::Tree { name, .. } => Some(name.clone()),
                     crate::Node::Args {
                         name: row, column, ..
                     } => {
-                        let (_, args) = self.0.get_current_tab()?;
+                        let (_, args) = state.get_current_tab(self.tree_edit)?;
                         let args = args.as_args()?;
                         let column = args.get_columns_raw().get(*column)?;
                         Some(format!("({row}, {column})"))
@@ -57,7 +171,7 @@ impl Widget for Drawer<'_, '_> {
         };
 
         let block = Block::default()
-            .title(self.0.title.clone())
+            .title(self.tree_edit.title.clone())
             .borders(Borders::ALL);
 
         let inner_area = block.inner(area);
@@ -74,13 +188,13 @@ impl Widget for Drawer<'_, '_> {
 
         let tabs = Tabs::new(tab_titles)
             .block(Block::default().borders(Borders::BOTTOM))
-            .style(self.0.state.style)
-            .highlight_style(if self.0.state.input.is_some() {
-                self.0.state.style
+            .style(self.style)
+            .highlight_style(if state.in_input_mode() {
+                self.style
             } else {
-                self.0.state.highlight_style
+                self.highlight_style
             });
-        let tabs = if let Some(index) = self.0.get_index_tab() {
+        let tabs = if let Some(index) = state.index_tab(&self.tree_edit.tabs) {
             tabs.select(index)
         } else {
             tabs
@@ -88,16 +202,18 @@ impl Widget for Drawer<'_, '_> {
 
         let path = Tabs::new(path_segments)
             .block(Block::default().borders(Borders::TOP))
-            .style(self.0.state.style)
+            .style(self.style)
             .divider(">")
-            .highlight_style(if self.0.state.input.is_some() {
-                self.0.state.style
+            .highlight_style(if state.in_input_mode() {
+                self.style
             } else {
-                self.0.state.highlight_style
+                self.highlight_style
             });
         let path = {
-            let len = self.0.position().len();
-            if len > 0 {
+            let len = state.position().len();
+            if state.in_search_mode() || state.in_confirm_mode() || state.in_create_mode() {
+                path.select(0)
+            } else if len > 0 {
                 path.select(len - 1)
             } else {
                 path
@@ -107,26 +223,212 @@ impl Widget for Drawer<'_, '_> {
         tabs.render(chunks[0], buf);
         path.render(chunks[2], buf);
 
-        let Some((_, tab)) = self.0.get_current_tab() else {
+        let Some((_, tab)) = state.get_current_tab(self.tree_edit) else {
             return;
         };
-        let rect = Rect {
-            x: 0,
-            y: 0,
-            width: 1 << 9,
-            height: chunks[1].height,
-        };
-        let mut temp_buf = Buffer::empty(rect);
-        let width = tab.render(rect, &mut temp_buf, (&self.0.state, &mut 1));
-
         let rect = chunks[1];
-        let offset = width.saturating_sub(rect.width + 1);
-        for y in 0..rect.height {
-            let y1 = rect.y + y;
-            for x in 1..width {
-                let x1 = rect.x + x;
-                *buf.get_mut(x1, y1) = temp_buf.get(offset + x, y).clone()
-            }
+        let mut widths = Vec::new();
+        tab.measure(
+            (
+                &state.context(self.style, self.highlight_style, self.match_style, &dirty),
+                &mut 1,
+            ),
+            &mut widths,
+        );
+        let focus = state
+            .position
+            .len()
+            .saturating_sub(2)
+            .min(widths.len().saturating_sub(1));
+        let mut scroll = state.scroll.min(focus);
+        while scroll < focus
+            && widths[scroll..=focus]
+                .iter()
+                .map(|w| *w as usize)
+                .sum::<usize>()
+                > rect.width as usize
+        {
+            scroll += 1;
         }
+        state.scroll = scroll;
+
+        tab.render(
+            rect,
+            buf,
+            (
+                &state.context(self.style, self.highlight_style, self.match_style, &dirty),
+                &mut 1,
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Args, Event, Node, Tree, TreeEdit, TreeEditState};
+    use tui::{buffer::Buffer, layout::Rect, widgets::StatefulWidget};
+
+    fn render<'a>(
+        tree_edit: &TreeEdit<'a>,
+        state: &mut TreeEditState<'a>,
+        width: u16,
+        height: u16,
+    ) -> String {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        tree_edit.widget().render(area, &mut buf, state);
+        buf.content
+            .chunks(width as usize)
+            .map(|line| {
+                line.iter()
+                    .map(|cell| cell.symbol.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    fn body(screen: &str) -> String {
+        let lines = screen.lines().collect::<Vec<_>>();
+        lines[3..lines.len() - 3].join("\n")
+    }
+
+    #[test]
+    fn create_prompt() {
+        let mut tree_edit = TreeEdit::new("Test")
+            .tab(
+                "Network".to_string(),
+                Tree::default().branch("eth0", Args::default()),
+            )
+            .template("Interface", || Args::default().into())
+            .template("Group", || Tree::default().into());
+        let mut state = TreeEditState::default();
+
+        state.transition(Event::NewBranch, &mut tree_edit);
+        state.transition(Event::NextItem, &mut tree_edit);
+        assert!(render(&tree_edit, &mut state, 60, 10)
+            .contains("Create \"Interface\" from Group? Enter to confirm"));
+
+        state.transition(Event::Cancel, &mut tree_edit);
+        state.transition(Event::CloneBranch, &mut tree_edit);
+        assert!(
+            render(&tree_edit, &mut state, 60, 10).contains("Create \"eth0 2\"? Enter to confirm")
+        );
+    }
+
+    #[test]
+    fn independent_states() {
+        let mut tree_edit = TreeEdit::new("Test")
+            .tab(
+                "First".to_string(),
+                Args::default()
+                    .names(["Port"])
+                    .columns(["Value"])
+                    .value("Port", "Value", 80u16),
+            )
+            .tab(
+                "Second".to_string(),
+                Args::default()
+                    .names(["Name"])
+                    .columns(["Value"])
+                    .value("Name", "Value", "Sensor"),
+            );
+        let mut first = TreeEditState::default();
+        let mut second = TreeEditState::default();
+
+        second.transition(Event::NextTab, &mut tree_edit);
+        let screen = render(&tree_edit, &mut first, 40, 10);
+        assert!(screen.contains("(Port, Value)") && !screen.contains("(Name, Value)"));
+        let screen = render(&tree_edit, &mut second, 40, 10);
+        assert!(screen.contains("(Name, Value)") && !screen.contains("(Port, Value)"));
+        assert_eq!(first.position().first(), Some(&Node::tree("First")));
+
+        tree_edit.tabs.remove("Second");
+        let screen = render(&tree_edit, &mut second, 40, 10);
+        assert!(screen.contains("(Port, Value)"));
+        assert_eq!(
+            second.position(),
+            &vec![Node::tree("First"), Node::args("Port", 0)]
+        );
+        assert_eq!(
+            first.position(),
+            &vec![Node::tree("First"), Node::args("Port", 0)]
+        );
+    }
+
+    #[test]
+    fn horizontal_scroll() {
+        let mut tree_edit = TreeEdit::new("Test").tab(
+            "Tab".to_string(),
+            Tree::default().branch(
+                "First level branch",
+                Tree::default().branch(
+                    "Second level branch",
+                    Tree::default().branch(
+                        "Third level branch",
+                        Args::default()
+                            .names(["Deep row"])
+                            .columns(["Value"])
+                            .value("Deep row", "Value", 1u8),
+                    ),
+                ),
+            ),
+        );
+        let mut state = TreeEditState::default();
+
+        let screen = body(&render(&tree_edit, &mut state, 40, 10));
+        assert!(screen.contains("First level branch"));
+        (0..3).for_each(|_| {
+            state.transition(Event::NextLevel, &mut tree_edit);
+        });
+        assert_eq!(state.position().last(), Some(&Node::args("Deep row", 0)));
+        let screen = body(&render(&tree_edit, &mut state, 40, 10));
+        assert!(screen.contains("Deep row"));
+        assert!(!screen.contains("First level branch"));
+
+        (0..3).for_each(|_| {
+            state.transition(Event::PreviousLevel, &mut tree_edit);
+        });
+        let screen = body(&render(&tree_edit, &mut state, 40, 10));
+        assert!(screen.contains("First level branch"));
+    }
+
+    #[test]
+    fn vertical_scroll() {
+        let names = (0..30)
+            .map(|index| format!("Row {index:02}"))
+            .collect::<Vec<_>>();
+        let args = names.iter().fold(
+            Args::default().names(&names).columns(["Value"]),
+            |args, name| args.value(name, "Value", 0u8),
+        );
+        let tree = names.iter().fold(Tree::default(), |tree, name| {
+            tree.branch(name, args.clone())
+        });
+        let mut tree_edit = TreeEdit::new("Test")
+            .tab("Rows".to_string(), args)
+            .tab("Branches".to_string(), tree);
+        let mut state = TreeEditState::default();
+
+        (0..25).for_each(|_| {
+            state.transition(Event::NextItem, &mut tree_edit);
+        });
+        let screen = body(&render(&tree_edit, &mut state, 40, 12));
+        assert!(screen.contains("Row 25"));
+        assert!(!screen.contains("Row 00"));
+        (0..20).for_each(|_| {
+            state.transition(Event::PreviousItem, &mut tree_edit);
+        });
+        let screen = body(&render(&tree_edit, &mut state, 40, 12));
+        assert!(screen.contains("Row 05"));
+        assert!(!screen.contains("Row 25"));
+
+        state.transition(Event::NextTab, &mut tree_edit);
+        (0..29).for_each(|_| {
+            state.transition(Event::NextItem, &mut tree_edit);
+        });
+        assert_eq!(state.position().get(1), Some(&Node::tree("Row 29")));
+        let screen = body(&render(&tree_edit, &mut state, 40, 12));
+        assert!(screen.lines().any(|line| line.contains(">>Row 29")));
+        assert!(!screen.lines().any(|line| line.starts_with("│  Row 00")));
     }
 }